@@ -1,5 +1,65 @@
+use crate::types::{DisplayData, DisplayDataAddress};
+
 /// The number of ROWS available (28-pin).
 pub const ROWS_SIZE: usize = 16;
 
 /// The number of COMMONS available.
 pub const COMMONS_SIZE: usize = 8;
+
+/// The number of bytes of display RAM on the chip, one byte of COMMONS per ROW.
+pub const DISPLAY_RAM_BYTES: usize = 16;
+
+/// Return the number of LEDs that can be driven, `ROWS_SIZE * COMMONS_SIZE`.
+///
+/// # Example
+///
+/// ```
+/// // Size an array with one entry per LED.
+/// let brightness = [0u8; ht16k33::leds_size()];
+///
+/// assert_eq!(128, brightness.len());
+/// ```
+pub const fn leds_size() -> usize {
+    ROWS_SIZE * COMMONS_SIZE
+}
+
+/// Return the number of bytes of display RAM needed to hold `leds` LEDs, rounded up to whole ROWS.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(ht16k33::DISPLAY_RAM_BYTES, ht16k33::display_ram_bytes(ht16k33::leds_size()));
+/// assert_eq!(2, ht16k33::display_ram_bytes(9));
+/// ```
+pub const fn display_ram_bytes(leds: usize) -> usize {
+    (leds + COMMONS_SIZE - 1) / COMMONS_SIZE
+}
+
+// Compile-time checks that the constants match the types that represent the chip.
+const _: () = assert!(ROWS_SIZE * COMMONS_SIZE == DISPLAY_RAM_BYTES * 8);
+const _: () = assert!(DisplayData::all().bits().count_ones() as usize == COMMONS_SIZE);
+const _: () = assert!(DisplayDataAddress::ROW_15.bits() as usize == ROWS_SIZE - 1);
+const _: () = assert!(core::mem::size_of::<[DisplayData; ROWS_SIZE]>() == DISPLAY_RAM_BYTES);
+
+/// The number of bytes of key data RAM.
+pub const KEY_DATA_SIZE: usize = 6;
+
+/// The number of key scan (KS) lines available.
+pub const KEY_SCANS_SIZE: usize = 3;
+
+/// The number of key (K) lines available.
+pub const KEY_LINES_SIZE: usize = 13;
+
+// Compile-time check that the key data RAM holds all the keys, two bytes per KS line.
+const _: () = assert!(KEY_DATA_SIZE == KEY_SCANS_SIZE * 2);
+const _: () = assert!(KEY_LINES_SIZE <= 16);
+
+// Key data RAM address pointer, key data is read starting here.
+pub(crate) const KEY_DATA_ADDRESS: u8 = 0b0100_0000;
+
+// INT flag address pointer.
+pub(crate) const INT_FLAG_ADDRESS: u8 = 0b0110_0000;
+
+// Factory test-mode command.
+#[cfg(feature = "testmode")]
+pub(crate) const TEST_MODE_COMMAND: u8 = 0b1101_1001;
@@ -0,0 +1,434 @@
+use crate::constants::{COMMONS_SIZE, ROWS_SIZE};
+use crate::types::DisplayData;
+
+/// Transforms a frame just before it is written to the HT16K33 display RAM.
+///
+/// Effects are applied to a copy of the display buffer by
+/// [write_display_buffer_effect()](struct.HT16K33.html#method.write_display_buffer_effect), so decorative effects
+/// compose with whatever drew the display buffer without it knowing about them. Chain effects with
+/// [then()](trait.Effect.html#method.then), they are applied in order.
+///
+/// Implemented for closures taking the frame and the time delta.
+///
+/// # Examples
+///
+/// ```
+/// use ht16k33::{DisplayData, Effect, Invert, Scanline, ROWS_SIZE};
+///
+/// let mut effect = Scanline::new(50).then(Invert);
+///
+/// let mut frame = [DisplayData::empty(); ROWS_SIZE];
+/// effect.apply(&mut frame, 0);
+///
+/// // The scanline lit row 0, and then everything was inverted.
+/// assert_eq!(DisplayData::empty(), frame[0]);
+/// assert_eq!(DisplayData::all(), frame[1]);
+/// ```
+pub trait Effect {
+    /// Transform the frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The frame to transform, in the display buffer order.
+    /// * `delta_ms` - The time since the effect was last applied, in milliseconds.
+    fn apply(&mut self, frame: &mut [DisplayData; ROWS_SIZE], delta_ms: u32);
+
+    /// Chain another effect, applied after this one.
+    ///
+    /// # Arguments
+    ///
+    /// * `next` - The effect to apply after this one.
+    fn then<N>(self, next: N) -> Chain<Self, N>
+    where
+        Self: Sized,
+        N: Effect,
+    {
+        Chain {
+            first: self,
+            second: next,
+        }
+    }
+}
+
+impl<F> Effect for F
+where
+    F: FnMut(&mut [DisplayData; ROWS_SIZE], u32),
+{
+    fn apply(&mut self, frame: &mut [DisplayData; ROWS_SIZE], delta_ms: u32) {
+        self(frame, delta_ms)
+    }
+}
+
+/// Two effects applied in order, see [then()](trait.Effect.html#method.then).
+#[derive(Clone, Debug)]
+pub struct Chain<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A, B> Effect for Chain<A, B>
+where
+    A: Effect,
+    B: Effect,
+{
+    fn apply(&mut self, frame: &mut [DisplayData; ROWS_SIZE], delta_ms: u32) {
+        self.first.apply(frame, delta_ms);
+        self.second.apply(frame, delta_ms);
+    }
+}
+
+/// Inverts every LED of the frame.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Invert;
+
+impl Effect for Invert {
+    fn apply(&mut self, frame: &mut [DisplayData; ROWS_SIZE], _delta_ms: u32) {
+        for row in frame.iter_mut() {
+            *row = !*row;
+        }
+    }
+}
+
+/// Lights a whole row that sweeps across the frame, moving to the next row every `row_ms`.
+#[derive(Clone, Debug)]
+pub struct Scanline {
+    row_ms: u32,
+    elapsed_ms: u32,
+    row: usize,
+}
+
+impl Scanline {
+    /// Create a scanline starting at row 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `row_ms` - The time the scanline stays on each row, `0` is treated as `1`.
+    pub const fn new(row_ms: u32) -> Self {
+        Scanline {
+            row_ms: if row_ms == 0 { 1 } else { row_ms },
+            elapsed_ms: 0,
+            row: 0,
+        }
+    }
+
+    /// Return the row the scanline is on.
+    pub fn row(&self) -> usize {
+        self.row
+    }
+}
+
+impl Effect for Scanline {
+    fn apply(&mut self, frame: &mut [DisplayData; ROWS_SIZE], delta_ms: u32) {
+        self.elapsed_ms = self.elapsed_ms.saturating_add(delta_ms);
+
+        let rows = self.elapsed_ms / self.row_ms;
+        self.elapsed_ms %= self.row_ms;
+        self.row = (self.row + (rows % ROWS_SIZE as u32) as usize) % ROWS_SIZE;
+
+        frame[self.row] = DisplayData::all();
+    }
+}
+
+/// Lights random LEDs, each staying lit for `decay_ms`, on top of the frame.
+///
+/// The random numbers come from a closure, so any random number generator can be used, e.g.
+/// `|| rng.next_u32()` for a `rand_core::RngCore`.
+///
+/// # Examples
+///
+/// ```
+/// use ht16k33::{DisplayData, Effect, Sparkle, ROWS_SIZE};
+///
+/// // A xorshift generator, good enough for decoration.
+/// let mut seed = 0x1234_5678u32;
+/// let rng = move || {
+///     seed ^= seed << 13;
+///     seed ^= seed >> 17;
+///     seed ^= seed << 5;
+///     seed
+/// };
+///
+/// // Light about 1 in 16 LEDs, each for 200ms.
+/// let mut sparkle = Sparkle::new(rng, 16, 200);
+///
+/// let mut frame = [DisplayData::empty(); ROWS_SIZE];
+/// sparkle.apply(&mut frame, 33);
+/// ```
+pub struct Sparkle<R> {
+    rng: R,
+    density: u8,
+    decay_ms: u16,
+    // The time each LED stays lit for.
+    remaining_ms: [[u16; COMMONS_SIZE]; ROWS_SIZE],
+}
+
+impl<R> Sparkle<R>
+where
+    R: FnMut() -> u32,
+{
+    /// Create a sparkle effect with no LEDs lit.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - Returns uniformly distributed random numbers.
+    /// * `density` - The chance of each unlit LED lighting up per frame, out of 256.
+    /// * `decay_ms` - The time each LED stays lit for.
+    pub fn new(rng: R, density: u8, decay_ms: u16) -> Self {
+        Sparkle {
+            rng,
+            density,
+            decay_ms,
+            remaining_ms: [[0; COMMONS_SIZE]; ROWS_SIZE],
+        }
+    }
+
+    /// Set the chance of each unlit LED lighting up per frame, out of 256.
+    pub fn set_density(&mut self, density: u8) {
+        self.density = density;
+    }
+
+    /// Set the time each LED stays lit for, LEDs that are already lit keep their time.
+    pub fn set_decay_ms(&mut self, decay_ms: u16) {
+        self.decay_ms = decay_ms;
+    }
+}
+
+impl<R> Effect for Sparkle<R>
+where
+    R: FnMut() -> u32,
+{
+    fn apply(&mut self, frame: &mut [DisplayData; ROWS_SIZE], delta_ms: u32) {
+        let delta_ms = delta_ms.min(u32::from(u16::MAX)) as u16;
+
+        for (row, commons) in self.remaining_ms.iter_mut().enumerate() {
+            for (common, remaining_ms) in commons.iter_mut().enumerate() {
+                *remaining_ms = remaining_ms.saturating_sub(delta_ms);
+
+                // Use the high bits, the low bits of simple generators are the least random.
+                if *remaining_ms == 0 && ((self.rng)() >> 24) < u32::from(self.density) {
+                    *remaining_ms = self.decay_ms;
+                }
+
+                if *remaining_ms > 0 {
+                    frame[row].insert(DisplayData::from_bits_truncate(1 << common));
+                }
+            }
+        }
+    }
+}
+
+/// Turns lit LEDs off at random, so the frame flickers and looks dimmer, e.g. like a failing neon sign.
+///
+/// The chip dimming is not changed, the effect only drops LEDs from the frame. The random numbers come from a
+/// closure, see [Sparkle](struct.Sparkle.html).
+///
+/// # Examples
+///
+/// ```
+/// use ht16k33::{DisplayData, Effect, Flicker, ROWS_SIZE};
+///
+/// // A xorshift generator, good enough for decoration.
+/// let mut seed = 0x1234_5678u32;
+/// let rng = move || {
+///     seed ^= seed << 13;
+///     seed ^= seed >> 17;
+///     seed ^= seed << 5;
+///     seed
+/// };
+///
+/// // Turn off about 1 in 4 lit LEDs every frame.
+/// let mut flicker = Flicker::new(rng, 64);
+///
+/// let mut frame = [DisplayData::all(); ROWS_SIZE];
+/// flicker.apply(&mut frame, 33);
+/// ```
+pub struct Flicker<R> {
+    rng: R,
+    density: u8,
+}
+
+impl<R> Flicker<R>
+where
+    R: FnMut() -> u32,
+{
+    /// Create a flicker effect.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - Returns uniformly distributed random numbers.
+    /// * `density` - The chance of each lit LED turning off per frame, out of 256.
+    pub fn new(rng: R, density: u8) -> Self {
+        Flicker { rng, density }
+    }
+
+    /// Set the chance of each lit LED turning off per frame, out of 256.
+    pub fn set_density(&mut self, density: u8) {
+        self.density = density;
+    }
+}
+
+impl<R> Effect for Flicker<R>
+where
+    R: FnMut() -> u32,
+{
+    fn apply(&mut self, frame: &mut [DisplayData; ROWS_SIZE], _delta_ms: u32) {
+        for row in frame.iter_mut() {
+            for common in 0..COMMONS_SIZE {
+                let led = DisplayData::from_bits_truncate(1 << common);
+
+                // Use the high bits, the low bits of simple generators are the least random.
+                if row.contains(led) && ((self.rng)() >> 24) < u32::from(self.density) {
+                    row.remove(led);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invert() {
+        let mut frame = [DisplayData::COMMON_3; ROWS_SIZE];
+
+        Invert.apply(&mut frame, 0);
+
+        assert_eq!([!DisplayData::COMMON_3; ROWS_SIZE], frame);
+    }
+
+    #[test]
+    fn scanline() {
+        let mut scanline = Scanline::new(10);
+        let mut frame = [DisplayData::empty(); ROWS_SIZE];
+
+        scanline.apply(&mut frame, 9);
+        assert_eq!(0, scanline.row());
+
+        scanline.apply(&mut frame, 1);
+        assert_eq!(1, scanline.row());
+        assert_eq!(DisplayData::all(), frame[1]);
+
+        // Wraps around after the last row.
+        scanline.apply(&mut frame, 10 * ROWS_SIZE as u32 + 25);
+        assert_eq!(3, scanline.row());
+    }
+
+    #[test]
+    fn scanline_zero() {
+        let mut scanline = Scanline::new(0);
+        let mut frame = [DisplayData::empty(); ROWS_SIZE];
+
+        for _ in 0..ROWS_SIZE {
+            scanline.apply(&mut frame, 1);
+        }
+
+        assert_eq!(0, scanline.row());
+        assert_eq!([DisplayData::all(); ROWS_SIZE], frame);
+    }
+
+    #[test]
+    fn sparkle() {
+        let mut values = [0u32, u32::MAX].iter().cycle().cloned();
+        let mut sparkle = Sparkle::new(|| values.next().unwrap(), 1, 20);
+
+        // Every other LED lights up.
+        let mut frame = [DisplayData::empty(); ROWS_SIZE];
+        sparkle.apply(&mut frame, 0);
+        assert_eq!(
+            [DisplayData::from_bits_truncate(0b0101_0101); ROWS_SIZE],
+            frame
+        );
+
+        // And stays lit until it decays, without lighting up others.
+        sparkle.set_density(0);
+
+        let mut frame = [DisplayData::empty(); ROWS_SIZE];
+        sparkle.apply(&mut frame, 19);
+        assert_eq!(
+            [DisplayData::from_bits_truncate(0b0101_0101); ROWS_SIZE],
+            frame
+        );
+
+        let mut frame = [DisplayData::COMMON_1; ROWS_SIZE];
+        sparkle.apply(&mut frame, 1);
+        assert_eq!([DisplayData::COMMON_1; ROWS_SIZE], frame);
+    }
+
+    #[test]
+    fn sparkle_density() {
+        let mut sparkle = Sparkle::new(|| u32::MAX, u8::MAX, 20);
+
+        // The density is out of 256, so the maximum value never lights up.
+        let mut frame = [DisplayData::empty(); ROWS_SIZE];
+        sparkle.apply(&mut frame, 0);
+        assert_eq!([DisplayData::empty(); ROWS_SIZE], frame);
+
+        // LEDs without a decay time are never lit.
+        let mut sparkle = Sparkle::new(|| 0, 1, 0);
+        sparkle.apply(&mut frame, 0);
+        assert_eq!([DisplayData::empty(); ROWS_SIZE], frame);
+    }
+
+    #[test]
+    fn flicker() {
+        let mut values = [0u32, u32::MAX].iter().cycle().cloned();
+        let mut flicker = Flicker::new(|| values.next().unwrap(), 1);
+
+        // Every other lit LED turns off.
+        let mut frame = [DisplayData::all(); ROWS_SIZE];
+        flicker.apply(&mut frame, 0);
+        assert_eq!(
+            [DisplayData::from_bits_truncate(0b1010_1010); ROWS_SIZE],
+            frame
+        );
+
+        // Unlit LEDs stay off and use no random numbers, so every other row's lit LED turns off.
+        let mut frame = [DisplayData::COMMON_1; ROWS_SIZE];
+        flicker.apply(&mut frame, 0);
+        for (row, data) in frame.iter().enumerate() {
+            if row % 2 == 0 {
+                assert_eq!(DisplayData::empty(), *data);
+            } else {
+                assert_eq!(DisplayData::COMMON_1, *data);
+            }
+        }
+
+        let mut frame = [DisplayData::COMMON_1; ROWS_SIZE];
+        flicker.set_density(0);
+        flicker.apply(&mut frame, 0);
+        assert_eq!([DisplayData::COMMON_1; ROWS_SIZE], frame);
+    }
+
+    #[test]
+    fn flicker_density() {
+        // The density is out of 256, so the maximum value never turns off.
+        let mut flicker = Flicker::new(|| u32::MAX, u8::MAX);
+
+        let mut frame = [DisplayData::all(); ROWS_SIZE];
+        flicker.apply(&mut frame, 0);
+        assert_eq!([DisplayData::all(); ROWS_SIZE], frame);
+    }
+
+    #[test]
+    fn chain() {
+        let mut calls = 0;
+        let mut frame = [DisplayData::empty(); ROWS_SIZE];
+
+        {
+            let mut effect = Invert.then(|frame: &mut [DisplayData; ROWS_SIZE], delta_ms: u32| {
+                assert_eq!(5, delta_ms);
+                assert_eq!(DisplayData::all(), frame[0]);
+                frame[0] = DisplayData::COMMON_1;
+                calls += 1;
+            });
+
+            effect.apply(&mut frame, 5);
+        }
+
+        assert_eq!(1, calls);
+        assert_eq!(DisplayData::COMMON_1, frame[0]);
+        assert_eq!(DisplayData::all(), frame[1]);
+    }
+}
@@ -2,6 +2,7 @@ use core::fmt;
 
 /// Errors encountered during validation.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum ValidationError {
     /// The value is too large.
     ValueTooLarge {
@@ -14,6 +15,31 @@ pub enum ValidationError {
         /// Whether the limit is inclusive or not.
         inclusive: bool,
     },
+    /// The range `[start, end)` is empty or extends beyond `limit`.
+    RangeOutOfBounds {
+        /// Name of the axis the range is on, e.g. `row`.
+        axis: &'static str,
+        /// Start of the range, inclusive.
+        start: u8,
+        /// End of the range, exclusive.
+        end: u8,
+        /// Limit that the range must end at or before.
+        limit: u8,
+    },
+    /// The value is given more than once, where each value must be unique.
+    DuplicateValue {
+        /// Name of the value.
+        name: &'static str,
+        /// Value that is duplicated.
+        value: u8,
+    },
+    /// The flash rate is between 3 Hz and 60 Hz, which can trigger photosensitive seizures.
+    UnsafeFlashRate {
+        /// How long the display is on for each flash, in milliseconds.
+        on_ms: u16,
+        /// How long the display is off for each flash, in milliseconds.
+        off_ms: u16,
+    },
 }
 
 #[cfg(feature = "std")]
@@ -35,6 +61,24 @@ impl fmt::Display for ValidationError {
                 "'{}' value [{}] must be less than (or equal: {}) [{}])",
                 name, value, limit, inclusive
             ),
+            ValidationError::RangeOutOfBounds {
+                axis,
+                start,
+                end,
+                limit,
+            } => write!(
+                f,
+                "'{}' range [{}, {}) must be non-empty and end at or before [{}]",
+                axis, start, end, limit
+            ),
+            ValidationError::DuplicateValue { name, value } => {
+                write!(f, "'{}' value [{}] must be unique", name, value)
+            }
+            ValidationError::UnsafeFlashRate { on_ms, off_ms } => write!(
+                f,
+                "flashing [{}]ms on and [{}]ms off must be slower than 3 Hz or faster than 60 Hz",
+                on_ms, off_ms
+            ),
         }
     }
 }
@@ -0,0 +1,120 @@
+use crate::errors::ValidationError;
+
+/// The shortest flash period, in milliseconds, that flashes at less than 3 Hz.
+///
+/// Flashing between 3 Hz and 60 Hz can trigger photosensitive seizures, see [`is_safe_flash_rate`].
+///
+/// [`is_safe_flash_rate`]: fn.is_safe_flash_rate.html
+pub const SAFE_FLASH_PERIOD_MS: u16 = 334;
+
+/// Return whether flashing the display `on_ms` on and `off_ms` off is outside the 3 Hz to 60 Hz range.
+///
+/// Flashing faster than 60 Hz is perceived as steady, and slower than 3 Hz is considered safe for photosensitive
+/// viewers. The hardware blink rates of [`Display`] are all 2 Hz or slower.
+///
+/// # Arguments
+///
+/// * `on_ms` - How long the display is on for each flash, in milliseconds.
+/// * `off_ms` - How long the display is off for each flash, in milliseconds.
+///
+/// # Examples
+///
+/// ```
+/// use ht16k33::is_safe_flash_rate;
+///
+/// // 5 Hz.
+/// assert!(!is_safe_flash_rate(100, 100));
+///
+/// // 1 Hz.
+/// assert!(is_safe_flash_rate(500, 500));
+/// ```
+///
+/// [`Display`]: struct.Display.html
+pub const fn is_safe_flash_rate(on_ms: u16, off_ms: u16) -> bool {
+    let period_ms = on_ms as u32 + off_ms as u32;
+
+    // Slower than 3 Hz, or faster than 60 Hz.
+    period_ms >= SAFE_FLASH_PERIOD_MS as u32 || period_ms * 60 < 1000
+}
+
+/// Check that flashing the display `on_ms` on and `off_ms` off is safe, see [`is_safe_flash_rate`].
+///
+/// # Arguments
+///
+/// * `on_ms` - How long the display is on for each flash, in milliseconds.
+/// * `off_ms` - How long the display is off for each flash, in milliseconds.
+///
+/// # Errors
+///
+/// Returns a [`ValidationError::UnsafeFlashRate`] if the flash rate is between 3 Hz and 60 Hz.
+///
+/// # Examples
+///
+/// ```
+/// use ht16k33::validate_flash_rate;
+///
+/// assert!(validate_flash_rate(50, 50).is_err());
+/// ```
+///
+/// [`is_safe_flash_rate`]: fn.is_safe_flash_rate.html
+/// [`ValidationError::UnsafeFlashRate`]: enum.ValidationError.html#variant.UnsafeFlashRate
+pub fn validate_flash_rate(on_ms: u16, off_ms: u16) -> Result<(), ValidationError> {
+    if is_safe_flash_rate(on_ms, off_ms) {
+        Ok(())
+    } else {
+        Err(ValidationError::UnsafeFlashRate { on_ms, off_ms })
+    }
+}
+
+// Stretch an unsafe flash rate to `SAFE_FLASH_PERIOD_MS`, keeping the on/off ratio.
+pub(crate) fn limit_flash_rate(on_ms: u16, off_ms: u16) -> (u16, u16) {
+    if is_safe_flash_rate(on_ms, off_ms) {
+        return (on_ms, off_ms);
+    }
+
+    let period_ms = on_ms as u32 + off_ms as u32;
+    let on_ms = ((on_ms as u32 * SAFE_FLASH_PERIOD_MS as u32 + period_ms - 1) / period_ms) as u16;
+
+    (on_ms, SAFE_FLASH_PERIOD_MS - on_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_safe_flash_rate() {
+        assert!(super::is_safe_flash_rate(0, 0));
+        assert!(super::is_safe_flash_rate(8, 8));
+        assert!(!super::is_safe_flash_rate(9, 8));
+        assert!(!super::is_safe_flash_rate(100, 100));
+        assert!(!super::is_safe_flash_rate(333, 0));
+        assert!(super::is_safe_flash_rate(334, 0));
+        assert!(super::is_safe_flash_rate(u16::MAX, u16::MAX));
+    }
+
+    #[test]
+    fn validate_flash_rate() {
+        assert!(super::validate_flash_rate(500, 500).is_ok());
+
+        match super::validate_flash_rate(100, 50) {
+            Err(ValidationError::UnsafeFlashRate { on_ms, off_ms }) => {
+                assert_eq!((100, 50), (on_ms, off_ms))
+            }
+            _ => panic!("Expected UnsafeFlashRate"),
+        }
+    }
+
+    #[test]
+    fn limit_flash_rate() {
+        assert_eq!((500, 500), super::limit_flash_rate(500, 500));
+        assert_eq!((167, 167), super::limit_flash_rate(100, 100));
+        assert_eq!((223, 111), super::limit_flash_rate(100, 50));
+        assert_eq!((334, 0), super::limit_flash_rate(20, 0));
+
+        for on_ms in 0..400 {
+            let (on_ms, off_ms) = super::limit_flash_rate(on_ms, 400 - on_ms);
+            assert!(super::is_safe_flash_rate(on_ms, off_ms));
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! Queries on frames of LEDs, e.g. the display buffer, for using a frame as both a render target and a
+//! collision map.
+//!
+//! The LEDs beyond the edges of a frame are always off, so a game can test positions just outside the display
+//! without bounds checks.
+//!
+//! # Examples
+//!
+//! ```
+//! # use ht16k33::ValidationError;
+//! use ht16k33::{frame, DisplayData, Region, ROWS_SIZE};
+//! # fn main() -> Result<(), ValidationError> {
+//!
+//! // A wall along the first ROW.
+//! let mut walls = [DisplayData::empty(); ROWS_SIZE];
+//! walls[0] = DisplayData::all();
+//!
+//! assert!(frame::get(&walls, 0, 3));
+//! assert!(!frame::get(&walls, 1, 3));
+//! assert_eq!(8, frame::count_set(&walls));
+//!
+//! // A 2x2 player in the corner hits the wall.
+//! assert!(frame::intersects(&walls, Region::new(0, 2, 0, 2)?));
+//!
+//! # Ok(())
+//! # }
+//! ```
+use crate::constants::{COMMONS_SIZE, ROWS_SIZE};
+use crate::types::{DisplayData, Region};
+
+/// Return whether the LED is on, LEDs beyond the edges of the frame are off.
+///
+/// # Arguments
+///
+/// * `frame` - The frame to query.
+/// * `row` - The ROW of the LED.
+/// * `common` - The COMMON of the LED.
+pub fn get(frame: &[DisplayData; ROWS_SIZE], row: usize, common: usize) -> bool {
+    row < ROWS_SIZE && common < COMMONS_SIZE && frame[row].bits() & (1 << common) != 0
+}
+
+/// Return the number of LEDs that are on.
+///
+/// # Arguments
+///
+/// * `frame` - The frame to count.
+pub fn count_set(frame: &[DisplayData; ROWS_SIZE]) -> u32 {
+    frame.iter().map(|row| row.bits().count_ones()).sum()
+}
+
+/// Return the number of LEDs that are on inside the region.
+///
+/// # Arguments
+///
+/// * `frame` - The frame to count.
+/// * `region` - The region to count in.
+pub fn count_set_in(frame: &[DisplayData; ROWS_SIZE], region: Region) -> u32 {
+    let commons = region.commons();
+
+    frame[region.row_start() as usize..region.row_end() as usize]
+        .iter()
+        .map(|row| (*row & commons).bits().count_ones())
+        .sum()
+}
+
+/// Return whether any LED inside the region is on.
+///
+/// # Arguments
+///
+/// * `frame` - The frame to test.
+/// * `region` - The region to test.
+pub fn intersects(frame: &[DisplayData; ROWS_SIZE], region: Region) -> bool {
+    count_set_in(frame, region) > 0
+}
+
+/// Return whether any LED is on in both frames, e.g. a sprite and a collision map.
+///
+/// # Arguments
+///
+/// * `frame` - The first frame.
+/// * `other` - The second frame.
+pub fn overlaps(frame: &[DisplayData; ROWS_SIZE], other: &[DisplayData; ROWS_SIZE]) -> bool {
+    frame
+        .iter()
+        .zip(other.iter())
+        .any(|(row, other_row)| row.intersects(*other_row))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMPTY: [DisplayData; ROWS_SIZE] = [DisplayData::empty(); ROWS_SIZE];
+
+    #[test]
+    fn get() {
+        let mut frame = EMPTY;
+        frame[3] = DisplayData::COMMON_5;
+
+        for row in 0..ROWS_SIZE + 2 {
+            for common in 0..COMMONS_SIZE + 2 {
+                assert_eq!(row == 3 && common == 5, super::get(&frame, row, common));
+            }
+        }
+
+        // Beyond the edges is always off.
+        let full = [DisplayData::all(); ROWS_SIZE];
+        assert!(super::get(&full, ROWS_SIZE - 1, COMMONS_SIZE - 1));
+        assert!(!super::get(&full, ROWS_SIZE, 0));
+        assert!(!super::get(&full, 0, COMMONS_SIZE));
+        assert!(!super::get(&full, usize::MAX, usize::MAX));
+    }
+
+    #[test]
+    fn count_set() {
+        assert_eq!(0, super::count_set(&EMPTY));
+        assert_eq!(
+            (ROWS_SIZE * COMMONS_SIZE) as u32,
+            super::count_set(&[DisplayData::all(); ROWS_SIZE])
+        );
+
+        let mut frame = EMPTY;
+        frame[0] = DisplayData::COMMON_0 | DisplayData::COMMON_7;
+        frame[15] = DisplayData::COMMON_3;
+        assert_eq!(3, super::count_set(&frame));
+    }
+
+    #[test]
+    fn count_set_in() {
+        let full = [DisplayData::all(); ROWS_SIZE];
+
+        assert_eq!(
+            6,
+            super::count_set_in(&full, Region::new(1, 3, 4, 7).unwrap())
+        );
+        assert_eq!(
+            super::count_set(&full),
+            super::count_set_in(&full, Region::all())
+        );
+
+        let mut frame = EMPTY;
+        frame[2] = DisplayData::COMMON_3 | DisplayData::COMMON_4;
+        assert_eq!(
+            1,
+            super::count_set_in(&frame, Region::new(0, 3, 4, 8).unwrap())
+        );
+        assert_eq!(
+            0,
+            super::count_set_in(&frame, Region::new(3, 16, 0, 8).unwrap())
+        );
+    }
+
+    #[test]
+    fn intersects() {
+        let mut frame = EMPTY;
+        frame[5] = DisplayData::COMMON_2;
+
+        assert!(super::intersects(&frame, Region::new(5, 6, 2, 3).unwrap()));
+        assert!(super::intersects(&frame, Region::all()));
+        assert!(!super::intersects(&frame, Region::new(5, 6, 3, 8).unwrap()));
+        assert!(!super::intersects(&EMPTY, Region::all()));
+    }
+
+    #[test]
+    fn overlaps() {
+        let mut sprite = EMPTY;
+        sprite[4] = DisplayData::COMMON_1 | DisplayData::COMMON_2;
+
+        let mut walls = EMPTY;
+        walls[4] = DisplayData::COMMON_3;
+        assert!(!super::overlaps(&sprite, &walls));
+
+        walls[4] |= DisplayData::COMMON_2;
+        assert!(super::overlaps(&sprite, &walls));
+        assert!(super::overlaps(&walls, &sprite));
+    }
+}
@@ -0,0 +1,244 @@
+//! # hil
+//!
+//! Assertion helpers for hardware-in-the-loop tests, which read back the display RAM and key RAM from a real
+//! chip and compare them against the expected state.
+//!
+//! Like `assert_eq!`, the helpers panic with a description of the differences when the chip does not match, so
+//! they can be used directly in bench test harnesses. I2C errors are returned instead.
+//!
+//! *Requires the `hil` feature.*
+//!
+//! # Examples
+//!
+//! ```
+//! # use failure::Error;
+//! # use ht16k33::i2c_mock::I2cMock;
+//! use ht16k33::{hil, LedLocation, HT16K33};
+//! # fn main() -> Result<(), Error> {
+//! # let mut i2c = I2cMock::new();
+//! # let address = 0u8;
+//!
+//! let mut ht16k33 = HT16K33::new(i2c, address);
+//! ht16k33.initialize()?;
+//!
+//! // Run the firmware code under test.
+//! ht16k33.set_led(LedLocation::new(1, 2)?, true)?;
+//!
+//! hil::assert_led(&mut ht16k33, LedLocation::new(1, 2)?, true)?;
+//! hil::assert_display_buffer_written(&mut ht16k33)?;
+//!
+//! # Ok(())
+//! # }
+//! ```
+use crate::constants::ROWS_SIZE;
+use crate::types::{DisplayData, KeyData, LedLocation};
+use crate::HT16K33;
+
+use embedded_hal::blocking::i2c::{Write, WriteRead};
+
+/// Assert that the chip's display RAM matches the `expected` rows.
+///
+/// The display RAM is read without changing the display buffer, and is converted to the display buffer order if
+/// a bit order or row map is set.
+///
+/// # Arguments
+///
+/// * `ht16k33` - The driver of the chip under test.
+/// * `expected` - The expected display RAM rows.
+///
+/// # Panics
+///
+/// Panics if the display RAM does not match, listing each mismatched row.
+///
+/// # Errors
+///
+/// Returns the I2C error if the display RAM cannot be read.
+pub fn assert_display_ram<I2C, E>(
+    ht16k33: &mut HT16K33<I2C>,
+    expected: &[DisplayData; ROWS_SIZE],
+) -> Result<(), E>
+where
+    I2C: Write<Error = E> + WriteRead<Error = E>,
+{
+    let actual = ht16k33.read_display_ram()?;
+
+    if actual != *expected {
+        panic!("display RAM mismatch: {}", RowsDiff(expected, &actual));
+    }
+
+    Ok(())
+}
+
+/// Assert that the chip's display RAM matches the driver's display buffer and canary, i.e. the buffer has been
+/// written.
+///
+/// # Arguments
+///
+/// * `ht16k33` - The driver of the chip under test.
+///
+/// # Panics
+///
+/// Panics if the display RAM does not match, listing each mismatched row.
+///
+/// # Errors
+///
+/// Returns the I2C error if the display RAM cannot be read.
+pub fn assert_display_buffer_written<I2C, E>(ht16k33: &mut HT16K33<I2C>) -> Result<(), E>
+where
+    I2C: Write<Error = E> + WriteRead<Error = E>,
+{
+    let mut expected = *ht16k33.display_buffer();
+
+    // The canary is always written with the display buffer.
+    if let Some(canary) = ht16k33.canary() {
+        expected[canary.row_as_index()].insert(canary.common);
+    }
+
+    assert_display_ram(ht16k33, &expected)
+}
+
+/// Assert that the LED at `location` is `enabled` in the chip's display RAM.
+///
+/// # Arguments
+///
+/// * `ht16k33` - The driver of the chip under test.
+/// * `location` - The LED location to check.
+/// * `enabled` - The expected LED state.
+///
+/// # Panics
+///
+/// Panics if the LED state does not match.
+///
+/// # Errors
+///
+/// Returns the I2C error if the display RAM cannot be read.
+pub fn assert_led<I2C, E>(
+    ht16k33: &mut HT16K33<I2C>,
+    location: LedLocation,
+    enabled: bool,
+) -> Result<(), E>
+where
+    I2C: Write<Error = E> + WriteRead<Error = E>,
+{
+    let actual = ht16k33.read_display_ram()?[location.row_as_index()].contains(location.common);
+
+    if actual != enabled {
+        panic!(
+            "LED {} mismatch: expected enabled [{}], actual enabled [{}]",
+            location, enabled, actual
+        );
+    }
+
+    Ok(())
+}
+
+/// Assert that the chip's key RAM matches the `expected` key data.
+///
+/// Reading the key RAM also updates the driver's key buffer and clears the INT flag on the chip.
+///
+/// # Arguments
+///
+/// * `ht16k33` - The driver of the chip under test.
+/// * `expected` - The expected key data.
+///
+/// # Panics
+///
+/// Panics if the key RAM does not match.
+///
+/// # Errors
+///
+/// Returns the I2C error if the key RAM cannot be read.
+pub fn assert_key_ram<I2C, E>(ht16k33: &mut HT16K33<I2C>, expected: KeyData) -> Result<(), E>
+where
+    I2C: Write<Error = E> + WriteRead<Error = E>,
+{
+    let actual = ht16k33.read_key_data()?;
+
+    if actual != expected {
+        panic!("key RAM mismatch: expected {}, actual {}", expected, actual);
+    }
+
+    Ok(())
+}
+
+// Formats the mismatched rows of two sets of display RAM rows.
+struct RowsDiff<'a>(&'a [DisplayData; ROWS_SIZE], &'a [DisplayData; ROWS_SIZE]);
+
+impl<'a> core::fmt::Display for RowsDiff<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        for (row, (expected, actual)) in self.0.iter().zip(self.1.iter()).enumerate() {
+            if expected != actual {
+                write!(
+                    f,
+                    "\n  row {}: expected {:#010b}, actual {:#010b}",
+                    row,
+                    expected.bits(),
+                    actual.bits()
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i2c_mock::I2cMock;
+
+    const ADDRESS: u8 = 0;
+
+    #[test]
+    fn assert_display_ram() {
+        let mut ht16k33 = HT16K33::new(I2cMock::new(), ADDRESS);
+
+        ht16k33.update_display_buffer(LedLocation::new(3, 4).unwrap(), true);
+
+        let mut expected = [DisplayData::empty(); ROWS_SIZE];
+        super::assert_display_ram(&mut ht16k33, &expected).unwrap();
+
+        ht16k33.write_display_buffer().unwrap();
+        expected[3] = DisplayData::COMMON_4;
+        super::assert_display_ram(&mut ht16k33, &expected).unwrap();
+        assert_display_buffer_written(&mut ht16k33).unwrap();
+        assert_led(&mut ht16k33, LedLocation::new(3, 4).unwrap(), true).unwrap();
+        assert_led(&mut ht16k33, LedLocation::new(3, 5).unwrap(), false).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "row 3: expected 0b00010000, actual 0b00000000")]
+    fn assert_display_buffer_not_written() {
+        let mut ht16k33 = HT16K33::new(I2cMock::new(), ADDRESS);
+
+        ht16k33.update_display_buffer(LedLocation::new(3, 4).unwrap(), true);
+
+        assert_display_buffer_written(&mut ht16k33).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "LED (row: 0, common: 0) mismatch")]
+    fn assert_led_mismatch() {
+        let mut ht16k33 = HT16K33::new(I2cMock::new(), ADDRESS);
+
+        assert_led(&mut ht16k33, LedLocation::new(0, 0).unwrap(), true).unwrap();
+    }
+
+    #[test]
+    fn assert_key_ram() {
+        let mut i2c = I2cMock::new();
+        i2c.key_values = [0, 0, 1, 0, 0, 0];
+
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        super::assert_key_ram(&mut ht16k33, KeyData::new([0, 0, 1, 0, 0, 0])).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "key RAM mismatch")]
+    fn assert_key_ram_mismatch() {
+        let mut ht16k33 = HT16K33::new(I2cMock::new(), ADDRESS);
+
+        super::assert_key_ram(&mut ht16k33, KeyData::new([1, 0, 0, 0, 0, 0])).unwrap();
+    }
+}
@@ -2,12 +2,19 @@
 //!
 //! A mock I2C library to support using the [HT16K33](../struct.HT16K33.html) driver on non-Linux systems that do
 //! not have I2C support.
+//!
+//! The mock emulates the HT16K33 chip: the display RAM, the write-only setup registers, the key RAM and INT flag,
+//! address auto-increment and wrap-around, and standby mode. It can be used in place of a real device when testing
+//! code built on this driver.
 use embedded_hal as hal;
 
 use core::fmt;
 
-use crate::constants::ROWS_SIZE;
-use crate::types::DisplayDataAddress;
+use crate::constants::{DISPLAY_RAM_BYTES, INT_FLAG_ADDRESS, KEY_DATA_ADDRESS, KEY_DATA_SIZE};
+use crate::types::{DeviceDefaults, Dimming, Display, DisplayDataAddress, Oscillator};
+
+// The last address of the key RAM.
+const KEY_DATA_LAST_ADDRESS: u8 = KEY_DATA_ADDRESS + KEY_DATA_SIZE as u8 - 1;
 
 /// Mock error to satisfy the I2C trait.
 #[derive(Debug)]
@@ -37,14 +44,109 @@ impl fmt::Display for I2cMockError {
 /// ```
 pub struct I2cMock {
     /// Display RAM state.
-    pub data_values: [u8; ROWS_SIZE],
+    pub data_values: [u8; DISPLAY_RAM_BYTES],
+    /// Key RAM state.
+    pub key_values: [u8; KEY_DATA_SIZE],
+    /// INT flag state, non-zero if there is new key data.
+    ///
+    /// Cleared when the key RAM is read.
+    pub int_flag: u8,
+    /// Oscillator register state.
+    pub oscillator: Oscillator,
+    /// Display register state.
+    pub display: Display,
+    /// Dimming register state.
+    pub dimming: Dimming,
 }
 
+/// The HT16K33 chip emulator.
+///
+/// The [`I2cMock`] emulates the whole chip, so this is the same type under the name used for device testing.
+///
+/// # Example
+///
+/// ```
+/// use ht16k33::i2c_mock::Ht16k33Emulator;
+/// use ht16k33::HT16K33;
+/// # fn main() {
+///
+/// let mut ht16k33 = HT16K33::new(Ht16k33Emulator::new(), 0u8);
+///
+/// # }
+/// ```
+///
+/// [`I2cMock`]: struct.I2cMock.html
+pub type Ht16k33Emulator = I2cMock;
+
 impl I2cMock {
     /// Create an I2cMock.
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         I2cMock {
-            data_values: [0; ROWS_SIZE],
+            data_values: [0; DISPLAY_RAM_BYTES],
+            key_values: [0; KEY_DATA_SIZE],
+            int_flag: 0,
+            oscillator: DeviceDefaults::POWER_ON.oscillator,
+            display: DeviceDefaults::POWER_ON.display,
+            dimming: DeviceDefaults::POWER_ON.dimming,
+        }
+    }
+
+    /// Return whether the LEDs are being displayed.
+    ///
+    /// The display is only driven when both the oscillator and the display are on.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ht16k33::i2c_mock::I2cMock;
+    /// # fn main() {
+    ///
+    /// let i2c_mock = I2cMock::new();
+    ///
+    /// // The Power-on Reset default is standby, with the display off.
+    /// assert!(!i2c_mock.is_displaying());
+    ///
+    /// # }
+    /// ```
+    pub fn is_displaying(&self) -> bool {
+        self.oscillator.contains(Oscillator::ON) && self.display.contains(Display::ON)
+    }
+
+    /// Emulate a keyscan that found the given key data.
+    ///
+    /// The key RAM is updated and the INT flag is set if any keys are pressed. The keyscan only runs while the
+    /// oscillator is on, so this is ignored in standby mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_values` - The scanned key data.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ht16k33::i2c_mock::I2cMock;
+    /// use ht16k33::Oscillator;
+    /// # fn main() {
+    ///
+    /// let mut i2c_mock = I2cMock::new();
+    /// i2c_mock.oscillator = Oscillator::ON;
+    ///
+    /// i2c_mock.scan_keys([0b0000_0001, 0, 0, 0, 0, 0]);
+    ///
+    /// assert_ne!(0, i2c_mock.int_flag);
+    ///
+    /// # }
+    /// ```
+    pub fn scan_keys(&mut self, key_values: [u8; KEY_DATA_SIZE]) {
+        if !self.oscillator.contains(Oscillator::ON) {
+            return;
+        }
+
+        self.key_values = key_values;
+
+        if key_values.iter().any(|value| *value != 0) {
+            self.int_flag = 0xFF;
         }
     }
 }
@@ -79,9 +181,39 @@ impl hal::blocking::i2c::WriteRead for I2cMock {
         bytes: &[u8],
         buffer: &mut [u8],
     ) -> Result<(), Self::Error> {
+        // There must be an address to read from.
+        if bytes.is_empty() {
+            return Err(I2cMockError);
+        }
+
+        if bytes[0] == INT_FLAG_ADDRESS {
+            for value in buffer.iter_mut() {
+                *value = self.int_flag;
+            }
+
+            return Ok(());
+        }
+
+        if let KEY_DATA_ADDRESS..=KEY_DATA_LAST_ADDRESS = bytes[0] {
+            let mut key_offset = (bytes[0] - KEY_DATA_ADDRESS) as usize;
+
+            for value in buffer.iter_mut() {
+                *value = self.key_values[key_offset];
+
+                // Emulate auto-increment and wrap-around for the key RAM too.
+                key_offset = (key_offset + 1) % self.key_values.len();
+            }
+
+            // Reading the key RAM clears the INT flag.
+            self.int_flag = 0;
+
+            return Ok(());
+        }
+
         // The `bytes` have the `data_address` command + index to start reading from,
         // need to clear the command to extract the starting index.
-        let mut data_offset = (bytes[0] ^ DisplayDataAddress::ROW_0.bits()) as usize;
+        let mut data_offset =
+            (bytes[0] ^ DisplayDataAddress::ROW_0.bits()) as usize % self.data_values.len();
 
         for value in buffer.iter_mut() {
             *value = self.data_values[data_offset];
@@ -121,14 +253,35 @@ impl hal::blocking::i2c::Write for I2cMock {
     /// # }
     /// ```
     fn write(&mut self, _address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
-        // "Command-only" writes are length 1 and write-only, and cannot be read back,
-        // discard them for simplicity.
+        // There must be a command or address to write.
+        if bytes.is_empty() {
+            return Err(I2cMockError);
+        }
+
+        // "Command-only" writes are length 1 and update the write-only setup registers.
         if bytes.len() == 1 {
+            let command = bytes[0];
+
+            if command & 0b1111_0000 == Oscillator::COMMAND.bits() {
+                self.oscillator = Oscillator::from_bits_truncate(command) & Oscillator::ON;
+            } else if command & 0b1111_0000 == Display::COMMAND.bits() {
+                self.display = Display::from_bits_truncate(command) & !Display::COMMAND;
+            } else if command & 0b1111_0000 == Dimming::COMMAND.bits() {
+                self.dimming =
+                    Dimming::from_bits_truncate(command & Dimming::BRIGHTNESS_MAX.bits());
+            }
+
+            // Other commands, e.g. only setting the address pointer, have no emulated effect.
             return Ok(());
         }
 
-        // Other writes have data, store them.
+        // Other writes have data, only the display RAM addresses store them. The key RAM and INT flag are
+        // read-only and the setup commands take no data, so the HT16K33 ignores the data, emulate that.
         let mut data_offset = (bytes[0] ^ DisplayDataAddress::ROW_0.bits()) as usize;
+        if data_offset >= self.data_values.len() {
+            return Ok(());
+        }
+
         let data = &bytes[1..];
 
         for value in data.iter() {
@@ -143,6 +296,8 @@ impl hal::blocking::i2c::Write for I2cMock {
 }
 
 #[cfg(test)]
+// The tests index the buffers by address to mirror the device layout.
+#[allow(clippy::needless_range_loop)]
 mod tests {
     use super::*;
     use hal::blocking::i2c::{Write, WriteRead};
@@ -154,6 +309,41 @@ mod tests {
         let _i2c_mock = I2cMock::new();
     }
 
+    #[test]
+    fn write_ignored_address() {
+        let mut i2c_mock = I2cMock::new();
+        i2c_mock.key_values[0] = 0b0000_0001;
+        i2c_mock.int_flag = 0xFF;
+
+        let addresses = [
+            DisplayDataAddress::ROW_15.bits() + 1,
+            KEY_DATA_ADDRESS,
+            KEY_DATA_LAST_ADDRESS,
+            INT_FLAG_ADDRESS,
+            (Oscillator::COMMAND | Oscillator::ON).bits(),
+            (Display::COMMAND | Display::ON).bits(),
+            (Dimming::COMMAND | Dimming::BRIGHTNESS_MIN).bits(),
+            0xFF,
+        ];
+
+        for &address in addresses.iter() {
+            i2c_mock.write(ADDRESS, &[address, 0xFF, 0xFF]).unwrap();
+
+            assert_eq!(
+                [0; DISPLAY_RAM_BYTES], i2c_mock.data_values,
+                "{:#04x}",
+                address
+            );
+        }
+
+        // Read-only and write-only state is unchanged too.
+        assert_eq!(0b0000_0001, i2c_mock.key_values[0]);
+        assert_eq!(0xFF, i2c_mock.int_flag);
+        assert_eq!(Oscillator::OFF, i2c_mock.oscillator);
+        assert_eq!(Display::OFF, i2c_mock.display);
+        assert_eq!(Dimming::BRIGHTNESS_MAX, i2c_mock.dimming);
+    }
+
     #[test]
     fn write() {
         let mut i2c_mock = I2cMock::new();
@@ -206,7 +396,7 @@ mod tests {
         let mut i2c_mock = I2cMock::new();
 
         // Match the data values size, +2 to wrap around, +1 for the data command.
-        let mut write_buffer = [1u8; super::ROWS_SIZE + 3];
+        let mut write_buffer = [1u8; super::DISPLAY_RAM_BYTES + 3];
         write_buffer[0] = super::DisplayDataAddress::ROW_0.bits();
 
         // These values should wrap and end up at indexes 0 & 1.
@@ -236,7 +426,7 @@ mod tests {
         let mut i2c_mock = I2cMock::new();
 
         // Match the data values size, +2 to wrap around, +1 for the data command.
-        let mut write_buffer = [1u8; super::ROWS_SIZE + 3];
+        let mut write_buffer = [1u8; super::DISPLAY_RAM_BYTES + 3];
 
         let offset = 4u8;
         write_buffer[0] = super::DisplayDataAddress::ROW_0.bits() | offset;
@@ -270,7 +460,7 @@ mod tests {
         i2c_mock.data_values[0] = 1;
         i2c_mock.data_values[1] = 1;
 
-        let mut read_buffer = [0u8; super::ROWS_SIZE];
+        let mut read_buffer = [0u8; super::DISPLAY_RAM_BYTES];
         i2c_mock
             .write_read(
                 ADDRESS,
@@ -336,7 +526,7 @@ mod tests {
         i2c_mock.data_values[2] = 1;
         i2c_mock.data_values[3] = 1;
 
-        let mut read_buffer = [0u8; super::ROWS_SIZE + 4];
+        let mut read_buffer = [0u8; super::DISPLAY_RAM_BYTES + 4];
 
         i2c_mock
             .write_read(
@@ -362,6 +552,73 @@ mod tests {
         }
     }
 
+    #[test]
+    fn write_read_int_flag() {
+        let mut i2c_mock = I2cMock::new();
+
+        i2c_mock.int_flag = 0xFF;
+
+        let mut read_buffer = [0u8; 1];
+        i2c_mock
+            .write_read(ADDRESS, &[super::INT_FLAG_ADDRESS], &mut read_buffer)
+            .unwrap();
+
+        assert_eq!(read_buffer[0], 0xFF);
+    }
+
+    #[test]
+    fn write_read_key_data() {
+        let mut i2c_mock = I2cMock::new();
+
+        i2c_mock.int_flag = 0xFF;
+        i2c_mock.key_values[1] = 1;
+        i2c_mock.key_values[5] = 1;
+
+        let mut read_buffer = [0u8; super::KEY_DATA_SIZE];
+        i2c_mock
+            .write_read(ADDRESS, &[super::KEY_DATA_ADDRESS], &mut read_buffer)
+            .unwrap();
+
+        assert_eq!(read_buffer, [0, 1, 0, 0, 0, 1]);
+
+        // Reading the key data clears the INT flag.
+        assert_eq!(i2c_mock.int_flag, 0);
+    }
+
+    #[test]
+    fn write_read_key_data_offset() {
+        let mut i2c_mock = I2cMock::new();
+
+        i2c_mock.key_values[5] = 1;
+
+        let mut read_buffer = [0u8; 2];
+        i2c_mock
+            .write_read(ADDRESS, &[KEY_DATA_LAST_ADDRESS], &mut read_buffer)
+            .unwrap();
+
+        // The key RAM wraps around to its first address.
+        assert_eq!(read_buffer, [1, 0]);
+    }
+
+    #[test]
+    fn write_read_key_data_range() {
+        let mut i2c_mock = I2cMock::new();
+
+        i2c_mock.int_flag = 0xFF;
+        i2c_mock.key_values = [0xFF; super::KEY_DATA_SIZE];
+
+        // Addresses that only share bits with the key RAM or INT flag addresses are not routed to them.
+        for &address in &[0x46u8, 0x4F, 0x61, 0x7F, 0xC0, 0xE0] {
+            let mut read_buffer = [0u8; 1];
+            i2c_mock
+                .write_read(ADDRESS, &[address], &mut read_buffer)
+                .unwrap();
+
+            assert_eq!(read_buffer[0], 0, "address [{:#04x}]", address);
+            assert_eq!(i2c_mock.int_flag, 0xFF, "address [{:#04x}]", address);
+        }
+    }
+
     #[test]
     fn write_read_wraparound_and_offset() {
         let mut i2c_mock = I2cMock::new();
@@ -369,7 +626,7 @@ mod tests {
         i2c_mock.data_values[0] = 1;
         i2c_mock.data_values[1] = 1;
 
-        let mut read_buffer = [0u8; super::ROWS_SIZE];
+        let mut read_buffer = [0u8; super::DISPLAY_RAM_BYTES];
 
         let offset = 4u8;
         i2c_mock
@@ -397,4 +654,100 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn write_oscillator() {
+        let mut i2c_mock = I2cMock::new();
+
+        i2c_mock
+            .write(ADDRESS, &[(Oscillator::COMMAND | Oscillator::ON).bits()])
+            .unwrap();
+        assert_eq!(i2c_mock.oscillator, Oscillator::ON);
+
+        i2c_mock
+            .write(ADDRESS, &[(Oscillator::COMMAND | Oscillator::OFF).bits()])
+            .unwrap();
+        assert_eq!(i2c_mock.oscillator, Oscillator::OFF);
+    }
+
+    #[test]
+    fn write_display() {
+        let mut i2c_mock = I2cMock::new();
+
+        i2c_mock
+            .write(ADDRESS, &[(Display::COMMAND | Display::TWO_HZ).bits()])
+            .unwrap();
+        assert_eq!(i2c_mock.display, Display::TWO_HZ);
+    }
+
+    #[test]
+    fn write_dimming() {
+        let mut i2c_mock = I2cMock::new();
+
+        i2c_mock
+            .write(
+                ADDRESS,
+                &[(Dimming::COMMAND | Dimming::BRIGHTNESS_3_16).bits()],
+            )
+            .unwrap();
+        assert_eq!(i2c_mock.dimming, Dimming::BRIGHTNESS_3_16);
+    }
+
+    #[test]
+    fn is_displaying() {
+        let mut i2c_mock = I2cMock::new();
+        assert!(!i2c_mock.is_displaying());
+
+        i2c_mock.display = Display::ON;
+        assert!(!i2c_mock.is_displaying(), "Standby does not display");
+
+        i2c_mock.oscillator = Oscillator::ON;
+        assert!(i2c_mock.is_displaying());
+    }
+
+    #[test]
+    fn scan_keys() {
+        let mut i2c_mock = I2cMock::new();
+
+        // Keyscan does not run in standby.
+        i2c_mock.scan_keys([1, 0, 0, 0, 0, 0]);
+        assert_eq!(i2c_mock.key_values, [0; KEY_DATA_SIZE]);
+        assert_eq!(i2c_mock.int_flag, 0);
+
+        i2c_mock.oscillator = Oscillator::ON;
+
+        i2c_mock.scan_keys([1, 0, 0, 0, 0, 0]);
+        assert_eq!(i2c_mock.key_values, [1, 0, 0, 0, 0, 0]);
+        assert_ne!(i2c_mock.int_flag, 0);
+    }
+
+    #[test]
+    fn write_empty() {
+        let mut i2c_mock = I2cMock::new();
+
+        assert!(i2c_mock.write(ADDRESS, &[]).is_err());
+    }
+
+    #[test]
+    fn write_read_empty() {
+        let mut i2c_mock = I2cMock::new();
+
+        let mut read_buffer = [0u8; 1];
+        assert!(i2c_mock.write_read(ADDRESS, &[], &mut read_buffer).is_err());
+    }
+
+    #[test]
+    fn no_panic() {
+        let mut i2c_mock = I2cMock::new();
+
+        // No address or command byte may panic.
+        for value in 0..=255u8 {
+            let mut read_buffer = [0u8; 2];
+
+            i2c_mock.write(ADDRESS, &[value, 1u8]).unwrap();
+            i2c_mock
+                .write_read(ADDRESS, &[value], &mut read_buffer)
+                .unwrap();
+        }
+    }
 }
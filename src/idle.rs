@@ -0,0 +1,312 @@
+use crate::types::{Dimming, Display};
+use crate::HT16K33;
+
+use core::fmt;
+use embedded_hal::blocking::i2c::{Write, WriteRead};
+
+/// The state of an [`IdleManager`].
+///
+/// [`IdleManager`]: struct.IdleManager.html
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum IdleState {
+    /// The display is shown as configured.
+    Active,
+    /// The display is dimmed after a period of inactivity.
+    Dimmed,
+    /// The display is turned off after a longer period of inactivity.
+    Blanked,
+}
+
+impl fmt::Display for IdleState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IdleState::Active => write!(f, "IdleState::Active"),
+            IdleState::Dimmed => write!(f, "IdleState::Dimmed"),
+            IdleState::Blanked => write!(f, "IdleState::Blanked"),
+        }
+    }
+}
+
+/// Dims and then blanks the display after a period of inactivity, like a screen saver.
+///
+/// Each call to [tick()] is one tick. A tick with pending display buffer changes, or a call to [wake()] (e.g. on a
+/// key press), is activity: it restores the previous brightness and display state, and restarts the count.
+///
+/// # Examples
+///
+/// ```
+/// # use failure::Error;
+/// # use ht16k33::i2c_mock::I2cMock;
+/// use ht16k33::{Dimming, IdleManager, IdleState, HT16K33};
+/// # fn main() -> Result<(), Error> {
+/// # let mut i2c = I2cMock::new();
+/// # let address = 0u8;
+///
+/// let mut ht16k33 = HT16K33::new(i2c, address);
+/// ht16k33.initialize()?;
+///
+/// // Dim after 100 ticks, and blank after 500 ticks.
+/// let mut idle = IdleManager::new(100, 500, Dimming::BRIGHTNESS_MIN);
+///
+/// loop {
+///     if ht16k33.keys_changed_since_last_poll(1)? {
+///         idle.wake(&mut ht16k33, |_| true)?;
+///     }
+///
+///     // Allow every state change.
+///     if let Some(state) = idle.tick(&mut ht16k33, |_| true)? {
+///         // Observe the new state.
+///     }
+///
+///     ht16k33.write_display_buffer()?;
+///     # if idle.state() == IdleState::Dimmed { break; }
+/// }
+///
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [tick()]: struct.IdleManager.html#method.tick
+/// [wake()]: struct.IdleManager.html#method.wake
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct IdleManager {
+    dim_after_ticks: u32,
+    blank_after_ticks: u32,
+    dim_level: Dimming,
+    idle_ticks: u32,
+    state: IdleState,
+    // The states to restore on activity.
+    dimming: Dimming,
+    display: Display,
+}
+
+impl IdleManager {
+    /// Create a new idle manager.
+    ///
+    /// # Arguments
+    ///
+    /// * `dim_after_ticks` - The number of inactive ticks before the display is dimmed.
+    /// * `blank_after_ticks` - The number of inactive ticks before the display is turned off.
+    /// * `dim_level` - The brightness of the dimmed display.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ht16k33::{Dimming, IdleManager};
+    ///
+    /// // Never blank, only dim.
+    /// let idle = IdleManager::new(100, u32::MAX, Dimming::BRIGHTNESS_2_16);
+    /// ```
+    pub const fn new(dim_after_ticks: u32, blank_after_ticks: u32, dim_level: Dimming) -> Self {
+        IdleManager {
+            dim_after_ticks,
+            blank_after_ticks,
+            dim_level,
+            idle_ticks: 0,
+            state: IdleState::Active,
+            dimming: Dimming::BRIGHTNESS_MAX,
+            display: Display::OFF,
+        }
+    }
+
+    /// Return the current state.
+    pub fn state(&self) -> IdleState {
+        self.state
+    }
+
+    /// Return the number of ticks since the last activity.
+    pub fn idle_ticks(&self) -> u32 {
+        self.idle_ticks
+    }
+
+    /// Count one tick, and dim or blank the display if it has been inactive for long enough.
+    ///
+    /// Pending display buffer changes are activity, which restores the display if it is dimmed or blanked.
+    ///
+    /// Returns the new state if it changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `ht16k33` - The driver to control.
+    /// * `hook` - Called with the new state before each state change; return `false` to veto the change, which
+    ///   is then retried on the next tick.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I2C error if the dimming or display state cannot be written, the state is not changed.
+    pub fn tick<I2C, E, F>(
+        &mut self,
+        ht16k33: &mut HT16K33<I2C>,
+        hook: F,
+    ) -> Result<Option<IdleState>, E>
+    where
+        I2C: Write<Error = E> + WriteRead<Error = E>,
+        F: FnMut(IdleState) -> bool,
+    {
+        if ht16k33.needs_flush() {
+            return self.wake(ht16k33, hook);
+        }
+
+        self.idle_ticks = self.idle_ticks.saturating_add(1);
+
+        let state = if self.idle_ticks >= self.blank_after_ticks {
+            IdleState::Blanked
+        } else if self.idle_ticks >= self.dim_after_ticks {
+            IdleState::Dimmed
+        } else {
+            IdleState::Active
+        };
+
+        self.change(ht16k33, state, hook)
+    }
+
+    /// Record activity, e.g. a key press, restoring the display if it is dimmed or blanked.
+    ///
+    /// Returns the new state if it changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `ht16k33` - The driver to control.
+    /// * `hook` - Called with the new state before the state changes; return `false` to veto the change.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I2C error if the dimming or display state cannot be written, the state is not changed.
+    pub fn wake<I2C, E, F>(
+        &mut self,
+        ht16k33: &mut HT16K33<I2C>,
+        hook: F,
+    ) -> Result<Option<IdleState>, E>
+    where
+        I2C: Write<Error = E> + WriteRead<Error = E>,
+        F: FnMut(IdleState) -> bool,
+    {
+        self.idle_ticks = 0;
+
+        self.change(ht16k33, IdleState::Active, hook)
+    }
+
+    fn change<I2C, E, F>(
+        &mut self,
+        ht16k33: &mut HT16K33<I2C>,
+        state: IdleState,
+        mut hook: F,
+    ) -> Result<Option<IdleState>, E>
+    where
+        I2C: Write<Error = E> + WriteRead<Error = E>,
+        F: FnMut(IdleState) -> bool,
+    {
+        if state == self.state || !hook(state) {
+            return Ok(None);
+        }
+
+        if self.state == IdleState::Active {
+            self.dimming = *ht16k33.dimming();
+            self.display = *ht16k33.display();
+        }
+
+        match state {
+            IdleState::Active => {
+                ht16k33.set_dimming(self.dimming)?;
+                ht16k33.set_display(self.display)?;
+            }
+            IdleState::Dimmed => {
+                ht16k33.set_display(self.display)?;
+                ht16k33.set_dimming(self.dim_level)?;
+            }
+            IdleState::Blanked => {
+                ht16k33.set_display(Display::OFF)?;
+            }
+        }
+
+        self.state = state;
+
+        Ok(Some(state))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i2c_mock::I2cMock;
+    use crate::LedLocation;
+
+    const ADDRESS: u8 = 0;
+
+    fn ht16k33() -> HT16K33<I2cMock> {
+        let mut ht16k33 = HT16K33::new(I2cMock::new(), ADDRESS);
+
+        ht16k33.initialize().unwrap();
+        ht16k33.set_display(Display::ON).unwrap();
+        ht16k33.set_dimming(Dimming::BRIGHTNESS_8_16).unwrap();
+
+        ht16k33
+    }
+
+    #[test]
+    fn tick() {
+        let mut ht16k33 = ht16k33();
+        let mut idle = IdleManager::new(2, 4, Dimming::BRIGHTNESS_MIN);
+
+        assert_eq!(None, idle.tick(&mut ht16k33, |_| true).unwrap());
+        assert_eq!(
+            Some(IdleState::Dimmed),
+            idle.tick(&mut ht16k33, |_| true).unwrap()
+        );
+        assert_eq!(Dimming::BRIGHTNESS_MIN, *ht16k33.dimming());
+
+        assert_eq!(None, idle.tick(&mut ht16k33, |_| true).unwrap());
+        assert_eq!(
+            Some(IdleState::Blanked),
+            idle.tick(&mut ht16k33, |_| true).unwrap()
+        );
+        assert_eq!(Display::OFF, *ht16k33.display());
+
+        // Updating the display buffer is activity.
+        ht16k33.update_display_buffer(LedLocation::new(0, 0).unwrap(), true);
+        assert_eq!(
+            Some(IdleState::Active),
+            idle.tick(&mut ht16k33, |_| true).unwrap()
+        );
+        assert_eq!(Dimming::BRIGHTNESS_8_16, *ht16k33.dimming());
+        assert_eq!(Display::ON, *ht16k33.display());
+        assert_eq!(0, idle.idle_ticks());
+
+        let i2c = ht16k33.destroy();
+        assert!(i2c.is_displaying());
+        assert_eq!(Dimming::BRIGHTNESS_8_16, i2c.dimming);
+    }
+
+    #[test]
+    fn wake() {
+        let mut ht16k33 = ht16k33();
+        let mut idle = IdleManager::new(1, 1, Dimming::BRIGHTNESS_MIN);
+
+        assert_eq!(None, idle.wake(&mut ht16k33, |_| true).unwrap());
+
+        idle.tick(&mut ht16k33, |_| true).unwrap();
+        assert_eq!(IdleState::Blanked, idle.state());
+
+        assert_eq!(
+            Some(IdleState::Active),
+            idle.wake(&mut ht16k33, |_| true).unwrap()
+        );
+        assert_eq!(Display::ON, *ht16k33.display());
+    }
+
+    #[test]
+    fn veto() {
+        let mut ht16k33 = ht16k33();
+        let mut idle = IdleManager::new(1, 2, Dimming::BRIGHTNESS_MIN);
+
+        // Never blank, but allow dimming.
+        let hook = |state| state != IdleState::Blanked;
+
+        idle.tick(&mut ht16k33, hook).unwrap();
+        idle.tick(&mut ht16k33, hook).unwrap();
+        idle.tick(&mut ht16k33, hook).unwrap();
+
+        assert_eq!(IdleState::Dimmed, idle.state());
+        assert_eq!(Display::ON, *ht16k33.display());
+    }
+}
@@ -0,0 +1,311 @@
+use crate::constants::KEY_DATA_SIZE;
+use crate::types::{KeyData, KeyLocation};
+use crate::HT16K33;
+
+use core::fmt;
+use embedded_hal::blocking::i2c::{Write, WriteRead};
+
+// The number of events the scanner can queue.
+const QUEUE_SIZE: usize = 16;
+
+/// A debounced change of a key's state.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum KeyEvent {
+    /// The key was pressed.
+    Pressed(KeyLocation),
+    /// The key was released.
+    Released(KeyLocation),
+}
+
+impl fmt::Display for KeyEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            KeyEvent::Pressed(location) => write!(f, "KeyEvent::Pressed{}", location),
+            KeyEvent::Released(location) => write!(f, "KeyEvent::Released{}", location),
+        }
+    }
+}
+
+/// Turns periodic key data reads into debounced [`KeyEvent`]s.
+///
+/// A change of the key data is only accepted once it has been read the same `debounce_reads` times in a row,
+/// which filters out contact bounce that the chip's own keyscan lets through. Call [scan()] periodically, at
+/// least as slowly as the chip scans the keys so that consecutive reads see different scans.
+///
+/// The events of each accepted change are queued, pressed keys before released keys, and are returned by
+/// [next_event()]. If the queue is full then new events are dropped, see [dropped_events()].
+///
+/// # Examples
+///
+/// ```
+/// # use failure::Error;
+/// # use ht16k33::i2c_mock::I2cMock;
+/// use ht16k33::{KeyEvent, KeyEventScanner, HT16K33};
+/// # fn main() -> Result<(), Error> {
+/// # let mut i2c = I2cMock::new();
+/// # i2c.oscillator = ht16k33::Oscillator::ON;
+/// # i2c.scan_keys([0b0000_0001, 0, 0, 0, 0, 0]);
+/// # let address = 0u8;
+///
+/// let mut ht16k33 = HT16K33::new(i2c, address);
+/// let mut scanner = KeyEventScanner::new(KeyEventScanner::DEFAULT_DEBOUNCE_READS);
+///
+/// loop {
+///     scanner.scan(&mut ht16k33)?;
+///
+///     while let Some(event) = scanner.next_event() {
+///         match event {
+///             KeyEvent::Pressed(location) => { /* ... */ }
+///             KeyEvent::Released(location) => { /* ... */ }
+///         }
+///         # return Ok(());
+///     }
+/// }
+///
+/// # }
+/// ```
+///
+/// [`KeyEvent`]: enum.KeyEvent.html
+/// [scan()]: struct.KeyEventScanner.html#method.scan
+/// [next_event()]: struct.KeyEventScanner.html#method.next_event
+/// [dropped_events()]: struct.KeyEventScanner.html#method.dropped_events
+#[derive(Clone, Debug)]
+pub struct KeyEventScanner {
+    debounce_reads: u8,
+    // The accepted key data, and the changed key data waiting to be accepted.
+    stable: KeyData,
+    candidate: KeyData,
+    candidate_reads: u8,
+    // Ring buffer of queued events.
+    queue: [Option<KeyEvent>; QUEUE_SIZE],
+    head: usize,
+    len: usize,
+    dropped_events: u32,
+}
+
+impl KeyEventScanner {
+    /// The default number of identical reads to accept a change.
+    pub const DEFAULT_DEBOUNCE_READS: u8 = 2;
+
+    /// Create a new scanner, with no keys pressed.
+    ///
+    /// # Arguments
+    ///
+    /// * `debounce_reads` - The number of identical reads in a row to accept a change, `0` is treated as `1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ht16k33::KeyEventScanner;
+    ///
+    /// // No debouncing, every change is accepted.
+    /// let scanner = KeyEventScanner::new(1);
+    /// ```
+    pub const fn new(debounce_reads: u8) -> Self {
+        KeyEventScanner {
+            debounce_reads: if debounce_reads == 0 {
+                1
+            } else {
+                debounce_reads
+            },
+            stable: KeyData::new([0; KEY_DATA_SIZE]),
+            candidate: KeyData::new([0; KEY_DATA_SIZE]),
+            candidate_reads: 0,
+            queue: [None; QUEUE_SIZE],
+            head: 0,
+            len: 0,
+            dropped_events: 0,
+        }
+    }
+
+    /// Return the accepted key data.
+    pub fn key_data(&self) -> &KeyData {
+        &self.stable
+    }
+
+    /// Return the number of events dropped because the queue was full.
+    pub fn dropped_events(&self) -> u32 {
+        self.dropped_events
+    }
+
+    /// Read the key data from the HT16K33 chip, and queue the events of an accepted change.
+    ///
+    /// # Arguments
+    ///
+    /// * `ht16k33` - The driver to read the key data with.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I2C error if the key data cannot be read, the read is then not counted.
+    pub fn scan<I2C, E>(&mut self, ht16k33: &mut HT16K33<I2C>) -> Result<(), E>
+    where
+        I2C: Write<Error = E> + WriteRead<Error = E>,
+    {
+        let key_data = ht16k33.read_key_data()?;
+
+        self.update(key_data);
+
+        Ok(())
+    }
+
+    /// Count a read of the key data, and queue the events of an accepted change.
+    ///
+    /// Use this instead of [scan()](struct.KeyEventScanner.html#method.scan) when the key data is read some
+    /// other way, e.g. by [service()](struct.HT16K33.html#method.service).
+    ///
+    /// # Arguments
+    ///
+    /// * `key_data` - The key data that was read.
+    pub fn update(&mut self, key_data: KeyData) {
+        if key_data == self.stable {
+            self.candidate_reads = 0;
+            return;
+        }
+
+        if key_data == self.candidate && self.candidate_reads > 0 {
+            self.candidate_reads = self.candidate_reads.saturating_add(1);
+        } else {
+            self.candidate = key_data;
+            self.candidate_reads = 1;
+        }
+
+        if self.candidate_reads < self.debounce_reads {
+            return;
+        }
+
+        let mut changed = [0u8; KEY_DATA_SIZE];
+        for (index, byte) in changed.iter_mut().enumerate() {
+            *byte = self.stable.bytes()[index] ^ key_data.bytes()[index];
+        }
+        let changed = KeyData::new(changed);
+
+        for location in changed
+            .pressed()
+            .filter(|&location| key_data.is_pressed(location))
+        {
+            self.push(KeyEvent::Pressed(location));
+        }
+
+        for location in changed
+            .pressed()
+            .filter(|&location| !key_data.is_pressed(location))
+        {
+            self.push(KeyEvent::Released(location));
+        }
+
+        self.stable = key_data;
+        self.candidate_reads = 0;
+    }
+
+    /// Return the oldest queued event, if any.
+    pub fn next_event(&mut self) -> Option<KeyEvent> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let event = self.queue[self.head].take();
+
+        self.head = (self.head + 1) % QUEUE_SIZE;
+        self.len -= 1;
+
+        event
+    }
+
+    fn push(&mut self, event: KeyEvent) {
+        if self.len == QUEUE_SIZE {
+            self.dropped_events = self.dropped_events.saturating_add(1);
+            return;
+        }
+
+        self.queue[(self.head + self.len) % QUEUE_SIZE] = Some(event);
+        self.len += 1;
+    }
+}
+
+impl Default for KeyEventScanner {
+    fn default() -> KeyEventScanner {
+        KeyEventScanner::new(KeyEventScanner::DEFAULT_DEBOUNCE_READS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i2c_mock::I2cMock;
+    use crate::Oscillator;
+
+    fn key(ks: u8, k: u8) -> KeyLocation {
+        KeyLocation::new(ks, k).unwrap()
+    }
+
+    #[test]
+    fn debounce() {
+        let mut scanner = KeyEventScanner::new(3);
+        let pressed = KeyData::new([0b0000_0001, 0, 0, 0, 0, 0]);
+
+        scanner.update(pressed);
+        scanner.update(pressed);
+        assert_eq!(None, scanner.next_event());
+
+        // A bounce restarts the count.
+        scanner.update(KeyData::default());
+        scanner.update(pressed);
+        scanner.update(pressed);
+        assert_eq!(None, scanner.next_event());
+
+        scanner.update(pressed);
+        assert_eq!(Some(KeyEvent::Pressed(key(0, 0))), scanner.next_event());
+        assert_eq!(None, scanner.next_event());
+        assert_eq!(&pressed, scanner.key_data());
+
+        // No change, no events.
+        scanner.update(pressed);
+        scanner.update(pressed);
+        scanner.update(pressed);
+        assert_eq!(None, scanner.next_event());
+    }
+
+    #[test]
+    fn pressed_and_released() {
+        let mut scanner = KeyEventScanner::new(1);
+
+        scanner.update(KeyData::new([0b0000_0001, 0, 0, 0, 0, 0]));
+        scanner.update(KeyData::new([0, 0, 0, 0b0001_0000, 0, 0]));
+
+        assert_eq!(Some(KeyEvent::Pressed(key(0, 0))), scanner.next_event());
+        assert_eq!(Some(KeyEvent::Pressed(key(1, 12))), scanner.next_event());
+        assert_eq!(Some(KeyEvent::Released(key(0, 0))), scanner.next_event());
+        assert_eq!(None, scanner.next_event());
+    }
+
+    #[test]
+    fn queue_full() {
+        let mut scanner = KeyEventScanner::new(0);
+
+        // 39 keys pressed, and then released.
+        scanner.update(KeyData::new([0xFF; KEY_DATA_SIZE]));
+        scanner.update(KeyData::default());
+
+        assert_eq!(39 * 2 - QUEUE_SIZE as u32, scanner.dropped_events());
+        assert_eq!(
+            QUEUE_SIZE,
+            core::iter::from_fn(|| scanner.next_event()).count()
+        );
+    }
+
+    #[test]
+    fn scan() {
+        let mut i2c = I2cMock::new();
+        i2c.oscillator = Oscillator::ON;
+        i2c.scan_keys([0, 0, 0b0000_0100, 0, 0, 0]);
+
+        let mut ht16k33 = HT16K33::new(i2c, 0);
+        let mut scanner = KeyEventScanner::default();
+
+        scanner.scan(&mut ht16k33).unwrap();
+        assert_eq!(None, scanner.next_event());
+
+        scanner.scan(&mut ht16k33).unwrap();
+        assert_eq!(Some(KeyEvent::Pressed(key(1, 2))), scanner.next_event());
+    }
+}
@@ -0,0 +1,237 @@
+use crate::types::LedLocation;
+use crate::HT16K33;
+
+/// A fixed group of LED locations that are controlled together, e.g. the status LEDs in each corner.
+///
+/// The group updates the display buffer, which must be written using `write_display_buffer()` for the change to
+/// be displayed.
+///
+/// # Example
+///
+/// ```
+/// # use ht16k33::i2c_mock::I2cMock;
+/// # use ht16k33::HT16K33;
+/// use ht16k33::{leds, LedGroup};
+/// # fn main() {
+/// # let mut i2c = I2cMock::new();
+/// # let address = 0u8;
+///
+/// const CORNERS: LedGroup<4> = LedGroup::new(leds![(0, 0), (0, 7), (15, 0), (15, 7)]);
+///
+/// let mut ht16k33 = HT16K33::new(i2c, address);
+///
+/// CORNERS.set_all(&mut ht16k33, true);
+/// assert!(CORNERS.all_on(&ht16k33));
+///
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct LedGroup<const N: usize> {
+    locations: [LedLocation; N],
+}
+
+impl<const N: usize> LedGroup<N> {
+    /// Create a group of the given LED locations.
+    ///
+    /// # Arguments
+    ///
+    /// * `locations` - The LED locations in the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ht16k33::{leds, LedGroup};
+    ///
+    /// const ROW_0: LedGroup<8> = LedGroup::new(leds![
+    ///     (0, 0),
+    ///     (0, 1),
+    ///     (0, 2),
+    ///     (0, 3),
+    ///     (0, 4),
+    ///     (0, 5),
+    ///     (0, 6),
+    ///     (0, 7)
+    /// ]);
+    /// ```
+    pub const fn new(locations: [LedLocation; N]) -> Self {
+        LedGroup { locations }
+    }
+
+    /// Return the LED locations in the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ht16k33::ValidationError;
+    /// use ht16k33::{leds, LedGroup, LedLocation};
+    /// # fn main() -> Result<(), ValidationError> {
+    ///
+    /// let group = LedGroup::new(leds![(2, 3)]);
+    ///
+    /// assert_eq!(&[LedLocation::new(2, 3)?], group.locations());
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub const fn locations(&self) -> &[LedLocation; N] {
+        &self.locations
+    }
+
+    /// Enable/disable all the LEDs of the group in the display buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `ht16k33` - The driver whose display buffer is updated.
+    /// * `enabled` - Set the LEDs on/off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::{leds, LedGroup, LedLocation};
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// const STATUS: LedGroup<2> = LedGroup::new(leds![(0, 0), (0, 1)]);
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    ///
+    /// STATUS.set_all(&mut ht16k33, true);
+    /// ht16k33.write_display_buffer()?;
+    ///
+    /// assert!(ht16k33.view().is_led_on(LedLocation::new(0, 1)?));
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_all<I2C>(&self, ht16k33: &mut HT16K33<I2C>, enabled: bool) {
+        for location in self.locations.iter() {
+            ht16k33.update_display_buffer(*location, enabled);
+        }
+    }
+
+    /// Return whether any LED of the group is enabled in the display buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `ht16k33` - The driver whose display buffer is checked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::{leds, LedGroup, LedLocation};
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// const STATUS: LedGroup<2> = LedGroup::new(leds![(0, 0), (0, 1)]);
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// assert!(!STATUS.any_on(&ht16k33));
+    ///
+    /// ht16k33.update_display_buffer(LedLocation::new(0, 1)?, true);
+    /// assert!(STATUS.any_on(&ht16k33));
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn any_on<I2C>(&self, ht16k33: &HT16K33<I2C>) -> bool {
+        let view = ht16k33.view();
+
+        self.locations
+            .iter()
+            .any(|location| view.is_led_on(*location))
+    }
+
+    /// Return whether all the LEDs of the group are enabled in the display buffer.
+    ///
+    /// An empty group is always on.
+    ///
+    /// # Arguments
+    ///
+    /// * `ht16k33` - The driver whose display buffer is checked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::{leds, LedGroup, LedLocation};
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// const STATUS: LedGroup<2> = LedGroup::new(leds![(0, 0), (0, 1)]);
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    ///
+    /// ht16k33.update_display_buffer(LedLocation::new(0, 1)?, true);
+    /// assert!(!STATUS.all_on(&ht16k33));
+    ///
+    /// ht16k33.update_display_buffer(LedLocation::new(0, 0)?, true);
+    /// assert!(STATUS.all_on(&ht16k33));
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn all_on<I2C>(&self, ht16k33: &HT16K33<I2C>) -> bool {
+        let view = ht16k33.view();
+
+        self.locations
+            .iter()
+            .all(|location| view.is_led_on(*location))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i2c_mock::I2cMock;
+    use crate::leds;
+
+    const GROUP: LedGroup<2> = LedGroup::new(leds![(1, 2), (3, 4)]);
+
+    #[test]
+    fn set_all() {
+        let mut ht16k33 = HT16K33::new(I2cMock::new(), 0);
+
+        assert!(!GROUP.any_on(&ht16k33));
+        assert!(!GROUP.all_on(&ht16k33));
+
+        GROUP.set_all(&mut ht16k33, true);
+        assert!(GROUP.any_on(&ht16k33));
+        assert!(GROUP.all_on(&ht16k33));
+
+        ht16k33.update_display_buffer(GROUP.locations()[0], false);
+        assert!(GROUP.any_on(&ht16k33));
+        assert!(!GROUP.all_on(&ht16k33));
+
+        GROUP.set_all(&mut ht16k33, false);
+        assert!(!GROUP.any_on(&ht16k33));
+    }
+
+    #[test]
+    fn borrowed_i2c() {
+        // No I2C device is needed to update or check the display buffer.
+        let mut ht16k33 = HT16K33::new((), 0);
+
+        GROUP.set_all(&mut ht16k33, true);
+        assert!(GROUP.all_on(&ht16k33));
+    }
+
+    #[test]
+    fn empty() {
+        let ht16k33 = HT16K33::new(I2cMock::new(), 0);
+        let group = LedGroup::new([]);
+
+        assert!(!group.any_on(&ht16k33));
+        assert!(group.all_on(&ht16k33));
+    }
+}
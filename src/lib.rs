@@ -10,10 +10,22 @@
 //! - [x] Supports `no_std` for embedded devices.
 //! - [ ] Supports all 20/24/28-pin SOP package types.
 //! - [x] Displays all 128 LEDs.
-//! - [ ] Reads keyscan.
+//! - [x] Reads keyscan.
 //! - [ ] Manages interrupts.
 //! - [ ] Manages slave devices.
 //!
+//! # Panics
+//!
+//! The value constructors, e.g. [`LedLocation::new`] and [`Dimming::from_u8`], do not panic on invalid input,
+//! invalid values are reported as a [`ValidationError`] instead. The exceptions are [`LedLocation::new_const`],
+//! which is intended for `const` contexts where the panic is a compile error, and the `_unchecked`
+//! constructors, which only check their input with `debug_assert!`.
+//!
+//! [`ValidationError`]: enum.ValidationError.html
+//! [`LedLocation::new`]: struct.LedLocation.html#method.new
+//! [`Dimming::from_u8`]: struct.Dimming.html#method.from_u8
+//! [`LedLocation::new_const`]: struct.LedLocation.html#method.new_const
+//!
 //! # Usage
 //!
 //! ## Linux-based platforms
@@ -74,19 +86,54 @@
 #![deny(missing_docs)]
 use embedded_hal as hal;
 
-#[cfg(feature = "serde")]
-use serde;
-
 mod constants;
+mod effects;
 mod errors;
+mod flash;
+mod idle;
+mod key_events;
+mod led_group;
+mod life;
+mod lite;
+mod macros;
+mod mirror;
+mod renderer;
+mod state;
 mod types;
 
+pub mod frame;
+#[cfg(feature = "hil")]
+pub mod hil;
 pub mod i2c_mock;
+pub mod timing;
 
+pub use effects::{Chain, Effect, Flicker, Invert, Scanline, Sparkle};
 pub use errors::ValidationError;
-pub use types::{Dimming, Display, DisplayData, DisplayDataAddress, LedLocation, Oscillator};
+pub use flash::{is_safe_flash_rate, validate_flash_rate, SAFE_FLASH_PERIOD_MS};
+pub use idle::{IdleManager, IdleState};
+pub use key_events::{KeyEvent, KeyEventScanner};
+pub use led_group::LedGroup;
+pub use life::{Edges, Life};
+pub use lite::Ht16k33Lite;
+pub use mirror::{Mirror, MirrorSink};
+pub use renderer::{RenderStats, Renderer};
+pub use state::Ht16k33State;
+pub use types::{
+    BitOrder, Capabilities, CommonIndexes, DeviceDefaults, Dimming, Display, DisplayData,
+    DisplayDataAddress, DisplayView, InitOptions, KeyData, KeyLocation, LedLocation, Oscillator,
+    PowerState, PressedKeys, RampMs, Region,
+};
 
-pub use constants::{COMMONS_SIZE, ROWS_SIZE};
+pub use constants::{
+    display_ram_bytes, leds_size, COMMONS_SIZE, DISPLAY_RAM_BYTES, KEY_DATA_SIZE, KEY_LINES_SIZE,
+    KEY_SCANS_SIZE, ROWS_SIZE,
+};
+
+use constants::{INT_FLAG_ADDRESS, KEY_DATA_ADDRESS};
+
+#[cfg(feature = "testmode")]
+use constants::TEST_MODE_COMMAND;
+use hal::blocking::delay::DelayMs;
 use hal::blocking::i2c::{Write, WriteRead};
 
 /// The HT16K33 state and configuration.
@@ -98,20 +145,48 @@ pub struct HT16K33<I2C> {
 
     // Represents the desired values of the device, may not match
     // the current values if it has not been written recently.
-    buffer: [DisplayData; ROWS_SIZE],
-
-    // The following values are write-only registers and cannot
-    // be queried from the device. We need to track their state
-    // here and synchronize them with the device.
-    oscillator_state: Oscillator,
-    display_state: Display,
-    dimming_state: Dimming,
+    state: Ht16k33State,
+
+    // The most recently read key data.
+    key_buffer: [u8; KEY_DATA_SIZE],
+
+    // The display buffer rows that may differ from the device's display RAM.
+    dirty: [bool; ROWS_SIZE],
+
+    // Maximum length of a single I2C write, including the address.
+    max_write_len: usize,
+
+    // Overlay layer displayed instead of the buffer, and the ticks until it expires.
+    overlay: Option<[DisplayData; ROWS_SIZE]>,
+    overlay_ticks: u32,
+
+    // Number of polls since the INT flag was last read.
+    poll_ticks: u32,
+
+    // The setup registers are write-only and cannot be queried
+    // from the device. The desired state is kept in `state` and
+    // updated by every setter, the confirmed state only once the
+    // value has been written successfully.
+    oscillator_confirmed: Oscillator,
+    display_confirmed: Display,
+    dimming_confirmed: Dimming,
+
+    // Whether the chip accepts the dimming command, some clone chips do not.
+    dimming_supported: bool,
+    // Whether the last dimming change was skipped because dimming is not supported.
+    dimming_ignored: bool,
+
+    // Whether the oscillator is turned on before operations that need it.
+    auto_wake: bool,
+
+    // Whether software flashing is limited to safe flash rates.
+    flash_guard: bool,
+
+    // Optional reserved LED location used to detect chip resets.
+    canary: Option<LedLocation>,
 }
 
-impl<I2C, E> HT16K33<I2C>
-where
-    I2C: Write<Error = E> + WriteRead<Error = E>,
-{
+impl<I2C> HT16K33<I2C> {
     /// Create an HT16K33 driver.
     ///
     /// # Arguments
@@ -140,14 +215,28 @@ where
         HT16K33 {
             address,
             i2c,
-            buffer: [DisplayData::empty(); ROWS_SIZE],
-            oscillator_state: Oscillator::OFF,
-            display_state: Display::OFF,
-            dimming_state: Dimming::BRIGHTNESS_MAX,
+            state: Ht16k33State::new(),
+            key_buffer: [0; KEY_DATA_SIZE],
+            // The display RAM is undefined at power-on.
+            dirty: [true; ROWS_SIZE],
+            overlay: None,
+            overlay_ticks: 0,
+            // The whole display buffer can be written at once.
+            max_write_len: ROWS_SIZE + 1,
+            // Never polled, so the first poll always reads the INT flag.
+            poll_ticks: u32::MAX,
+            oscillator_confirmed: DeviceDefaults::POWER_ON.oscillator,
+            display_confirmed: DeviceDefaults::POWER_ON.display,
+            dimming_confirmed: DeviceDefaults::POWER_ON.dimming,
+            dimming_supported: true,
+            dimming_ignored: false,
+            auto_wake: false,
+            flash_guard: false,
+            canary: None,
         }
     }
 
-    /// Initialize the HT16K33.
+    /// Return the given I2C device, making this device unusable.
     ///
     /// # Examples
     ///
@@ -155,32 +244,95 @@ where
     /// # use failure::Error;
     /// # use ht16k33::i2c_mock::I2cMock;
     /// # use ht16k33::HT16K33;
-    /// # fn main() -> Result<(), Error> {
+    /// # fn main() {
     /// # let mut i2c = I2cMock::new();
     /// # let address = 0u8;
     ///
     /// let mut ht16k33 = HT16K33::new(i2c, address);
-    /// ht16k33.initialize()?;
+    /// i2c = ht16k33.destroy();
     ///
-    /// # Ok(())
     /// # }
     /// ```
-    pub fn initialize(&mut self) -> Result<(), E> {
-        // Enable the oscillator so we can use the device.
-        self.set_oscillator(Oscillator::ON)?;
+    pub fn destroy(self) -> I2C {
+        // TODO Improve the naming? And somehow mark the state as destroyed so that
+        // all other I2C accesses fail nicely? Should we continue to allow non-I2C access?
+        self.i2c
+    }
 
-        // Set all values to match their defaults.
-        self.set_display(Display::OFF)?;
-        self.set_dimming(Dimming::BRIGHTNESS_MAX)?;
+    /// Return the current display buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # fn main() {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// let &buffer = ht16k33.display_buffer();
+    ///
+    /// # }
+    /// ```
+    pub fn display_buffer(&self) -> &[DisplayData; ROWS_SIZE] {
+        self.state.display_buffer()
+    }
 
-        // And clear the display.
-        self.clear_display_buffer();
-        self.write_display_buffer()?;
+    /// Return a read-only view of the display state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # use ht16k33::ValidationError;
+    /// use ht16k33::LedLocation;
+    /// # fn main() -> Result<(), ValidationError> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    ///
+    /// let view = ht16k33.view();
+    /// let enabled = view.is_led_on(LedLocation::new(0, 0)?);
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn view(&self) -> DisplayView<'_> {
+        DisplayView::new(
+            self.state.display_buffer(),
+            *self.state.oscillator(),
+            *self.state.display(),
+            *self.state.dimming(),
+        )
+    }
 
-        Ok(())
+    /// Return the display buffer, board wiring, and desired setup register state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # fn main() {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    ///
+    /// let bytes = ht16k33.state().display_ram_bytes();
+    ///
+    /// # }
+    /// ```
+    pub fn state(&self) -> &Ht16k33State {
+        &self.state
     }
 
-    /// Return the given I2C device, making this device unusable.
+    /// Return the most recently read key data.
     ///
     /// # Examples
     ///
@@ -193,17 +345,45 @@ where
     /// # let address = 0u8;
     ///
     /// let mut ht16k33 = HT16K33::new(i2c, address);
-    /// i2c = ht16k33.destroy();
+    /// let &key_buffer = ht16k33.key_buffer();
     ///
     /// # }
     /// ```
-    pub fn destroy(self) -> I2C {
-        // TODO Improve the naming? And somehow mark the state as destroyed so that
-        // all other I2C accesses fail nicely? Should we continue to allow non-I2C access?
-        self.i2c
+    pub fn key_buffer(&self) -> &[u8; KEY_DATA_SIZE] {
+        &self.key_buffer
     }
 
-    /// Return the current display buffer.
+    /// Return whether the display buffer may differ from the HT16K33 chip's display RAM.
+    ///
+    /// This is `true` when the buffer has been changed since it was last written, or if the last write failed,
+    /// in which case the chip may hold a partially written buffer. Write the buffer using
+    /// [write_display_buffer()](struct.HT16K33.html#method.write_display_buffer) to synchronize them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    ///
+    /// if ht16k33.needs_flush() {
+    ///     ht16k33.write_display_buffer()?;
+    /// }
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn needs_flush(&self) -> bool {
+        self.dirty.iter().any(|&dirty| dirty)
+    }
+
+    /// Return the display buffer rows that may differ from the display RAM, i.e. the rows that
+    /// [write_dirty()](struct.HT16K33.html#method.write_dirty) writes.
     ///
     /// # Examples
     ///
@@ -211,17 +391,47 @@ where
     /// # use failure::Error;
     /// # use ht16k33::i2c_mock::I2cMock;
     /// # use ht16k33::HT16K33;
+    /// use ht16k33::LedLocation;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// ht16k33.write_display_buffer()?;
+    ///
+    /// ht16k33.update_display_buffer(LedLocation::new(3, 0)?, true);
+    ///
+    /// assert!(ht16k33.dirty_rows()[3]);
+    /// assert!(!ht16k33.dirty_rows()[4]);
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn dirty_rows(&self) -> &[bool; ROWS_SIZE] {
+        &self.dirty
+    }
+
+    /// Return what the HT16K33 package supports.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
     /// # fn main() {
     /// # let mut i2c = I2cMock::new();
     /// # let address = 0u8;
     ///
     /// let mut ht16k33 = HT16K33::new(i2c, address);
-    /// let &buffer = ht16k33.display_buffer();
+    ///
+    /// if ht16k33.capabilities().keys > 0 {
+    ///     // Scan the keys.
+    /// }
     ///
     /// # }
     /// ```
-    pub fn display_buffer(&self) -> &[DisplayData; ROWS_SIZE] {
-        &self.buffer
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities::SOP28
     }
 
     /// Return the current oscillator state.
@@ -242,7 +452,7 @@ where
     /// # }
     /// ```
     pub fn oscillator(&self) -> &Oscillator {
-        &self.oscillator_state
+        self.state.oscillator()
     }
 
     /// Return the current display state.
@@ -263,7 +473,7 @@ where
     /// # }
     /// ```
     pub fn display(&self) -> &Display {
-        &self.display_state
+        self.state.display()
     }
 
     /// Return the current dimming state.
@@ -284,49 +494,34 @@ where
     /// # }
     /// ```
     pub fn dimming(&self) -> &Dimming {
-        &self.dimming_state
+        self.state.dimming()
     }
 
-    /// Enable/disable an LED address in the display buffer.
-    ///
-    /// The buffer must be written using [write_display_buffer()](struct.HT16K33.html#method.write_display_buffer)
-    /// for the change to be displayed.
-    ///
-    /// # Arguments
+    /// Return the oscillator state that was last written successfully to the chip.
     ///
-    /// * `location` - The LED location to update.
-    /// * `enabled` - Set the LED on (true) or off (false).
+    /// This differs from the [oscillator()](struct.HT16K33.html#method.oscillator) state if writing it failed.
     ///
     /// # Examples
     ///
     /// ```
     /// # use ht16k33::i2c_mock::I2cMock;
     /// # use ht16k33::HT16K33;
-    /// # use ht16k33::ValidationError;
-    /// use ht16k33::LedLocation;
-    /// # fn main() -> Result<(), ValidationError> {
+    /// # fn main() {
     /// # let mut i2c = I2cMock::new();
     /// # let address = 0u8;
     ///
     /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// let oscillator = ht16k33.confirmed_oscillator();
     ///
-    /// let led_location = LedLocation::new(0, 0)?;
-    /// ht16k33.update_display_buffer(led_location, true);
-    ///
-    /// # Ok(())
     /// # }
     /// ```
-    pub fn update_display_buffer(&mut self, location: LedLocation, enabled: bool) {
-        // TODO Validate `address` parameter.
-
-        // Turn on/off the specified LED.
-        self.buffer[location.row_as_index()].set(location.common, enabled);
+    pub fn confirmed_oscillator(&self) -> &Oscillator {
+        &self.oscillator_confirmed
     }
 
-    /// Clear contents of the display buffer.
+    /// Return the display state that was last written successfully to the chip.
     ///
-    /// The buffer must be written using [write_display_buffer()](struct.HT16K33.html#method.write_display_buffer)
-    /// for the change to be displayed.
+    /// This differs from the [display()](struct.HT16K33.html#method.display) state if writing it failed.
     ///
     /// # Examples
     ///
@@ -338,126 +533,89 @@ where
     /// # let address = 0u8;
     ///
     /// let mut ht16k33 = HT16K33::new(i2c, address);
-    /// ht16k33.clear_display_buffer();
+    /// let display = ht16k33.confirmed_display();
     ///
     /// # }
     /// ```
-    pub fn clear_display_buffer(&mut self) {
-        // TODO is there any advantage to iteration vs just assigning
-        // a new, empty `[0; ROWS_SIZE]` array?
-        for row in self.buffer.iter_mut() {
-            *row = DisplayData::COMMON_NONE;
-        }
+    pub fn confirmed_display(&self) -> &Display {
+        &self.display_confirmed
     }
 
-    /// Control the oscillator.
+    /// Return the dimming state that was last written successfully to the chip.
     ///
-    /// # Arguments
-    ///
-    /// * `oscillator` - Set the oscillator On/Off.
+    /// This differs from the [dimming()](struct.HT16K33.html#method.dimming) state if writing it failed.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use failure::Error;
     /// # use ht16k33::i2c_mock::I2cMock;
     /// # use ht16k33::HT16K33;
-    /// use ht16k33::Oscillator;
-    /// # fn main() -> Result<(), Error> {
+    /// # fn main() {
     /// # let mut i2c = I2cMock::new();
     /// # let address = 0u8;
     ///
     /// let mut ht16k33 = HT16K33::new(i2c, address);
-    /// ht16k33.set_oscillator(Oscillator::ON)?;
+    /// let dimming = ht16k33.confirmed_dimming();
     ///
-    /// # Ok(())
     /// # }
     /// ```
-    pub fn set_oscillator(&mut self, oscillator: Oscillator) -> Result<(), E> {
-        self.oscillator_state = oscillator;
-
-        self.i2c.write(
-            self.address,
-            &[(Oscillator::COMMAND | self.oscillator_state).bits()],
-        )?;
-
-        Ok(())
+    pub fn confirmed_dimming(&self) -> &Dimming {
+        &self.dimming_confirmed
     }
 
-    /// Control the display.
-    ///
-    /// # Arguments
-    ///
-    /// * `display` - Set the display On/Off.
+    /// Return the power state that was last written successfully to the chip.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use failure::Error;
     /// # use ht16k33::i2c_mock::I2cMock;
     /// # use ht16k33::HT16K33;
-    /// use ht16k33::Display;
-    /// # fn main() -> Result<(), Error> {
+    /// use ht16k33::PowerState;
+    /// # fn main() {
     /// # let mut i2c = I2cMock::new();
     /// # let address = 0u8;
     ///
     /// let mut ht16k33 = HT16K33::new(i2c, address);
-    /// ht16k33.set_display(Display::HALF_HZ)?;
+    /// assert_eq!(PowerState::Standby, ht16k33.power_state());
     ///
-    /// # Ok(())
     /// # }
     /// ```
-    pub fn set_display(&mut self, display: Display) -> Result<(), E> {
-        self.display_state = display;
-
-        self.i2c.write(
-            self.address,
-            &[(Display::COMMAND | self.display_state).bits()],
-        )?;
-
-        Ok(())
+    pub fn power_state(&self) -> PowerState {
+        PowerState::from(self.oscillator_confirmed)
     }
 
-    /// Control the display dimming.
-    ///
-    /// # Arguments
-    ///
-    /// * `dimming` - Set the dimming brightness.
+    /// Return whether the chip is woken up automatically, see [set_auto_wake()](struct.HT16K33.html#method.set_auto_wake).
     ///
     /// # Examples
     ///
     /// ```
-    /// # use failure::Error;
     /// # use ht16k33::i2c_mock::I2cMock;
     /// # use ht16k33::HT16K33;
-    /// use ht16k33::Dimming;
-    /// # fn main() -> Result<(), Error> {
+    /// # fn main() {
     /// # let mut i2c = I2cMock::new();
     /// # let address = 0u8;
     ///
     /// let mut ht16k33 = HT16K33::new(i2c, address);
-    /// ht16k33.set_dimming(Dimming::from_u8(4)?)?;
+    /// assert!(!ht16k33.auto_wake());
     ///
-    /// # Ok(())
     /// # }
     /// ```
-    pub fn set_dimming(&mut self, dimming: Dimming) -> Result<(), E> {
-        self.dimming_state = dimming;
-
-        self.i2c.write(
-            self.address,
-            &[(Dimming::COMMAND | self.dimming_state).bits()],
-        )?;
-
-        Ok(())
+    pub fn auto_wake(&self) -> bool {
+        self.auto_wake
     }
 
-    /// Control an LED.
+    /// Wake the chip automatically before operations that need the oscillator.
+    ///
+    /// In [standby](enum.PowerState.html#variant.Standby) the display RAM can still be written, but nothing is
+    /// shown and keyscan is stopped. With auto-wake enabled, turning the display on, writing the display buffer
+    /// and reading the key data first turn the oscillator on if the chip is in standby.
+    ///
+    /// *NOTE: Auto-wake is disabled by default, so the oscillator is only changed by
+    /// [set_oscillator()](struct.HT16K33.html#method.set_oscillator).*
     ///
     /// # Arguments
     ///
-    /// * `location` - The LED location to update.
-    /// * `enabled` - Set the LED on (true) or off (false).
+    /// * `enabled` - Whether the oscillator is turned on automatically.
     ///
     /// # Examples
     ///
@@ -465,355 +623,3900 @@ where
     /// # use failure::Error;
     /// # use ht16k33::i2c_mock::I2cMock;
     /// # use ht16k33::HT16K33;
-    /// use ht16k33::LedLocation;
+    /// use ht16k33::{LedLocation, PowerState};
     /// # fn main() -> Result<(), Error> {
     /// # let mut i2c = I2cMock::new();
     /// # let address = 0u8;
     ///
     /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// ht16k33.set_auto_wake(true);
     ///
-    /// let led_location = LedLocation::new(0, 0)?;
-    /// ht16k33.set_led(led_location, true)?;
+    /// ht16k33.set_led(LedLocation::new(0, 0)?, true)?;
+    /// assert_eq!(PowerState::Normal, ht16k33.power_state());
     ///
     /// # Ok(())
     /// # }
     /// ```
-    pub fn set_led(&mut self, location: LedLocation, enabled: bool) -> Result<(), E> {
-        // TODO Validate `address` parameter.
-        self.update_display_buffer(location, enabled);
-
-        self.i2c.write(
-            self.address,
-            &[
-                location.row.bits(),
-                self.buffer[location.row_as_index()].bits(),
-            ],
-        )?;
-
-        Ok(())
+    pub fn set_auto_wake(&mut self, enabled: bool) {
+        self.auto_wake = enabled;
     }
 
-    /// Write the display buffer to the HT16K33 chip.
+    /// Return whether the oscillator, display, and dimming states have all been written successfully to the chip.
+    ///
+    /// Dimming is ignored if it is not supported, see [set_dimming_supported()](struct.HT16K33.html#method.set_dimming_supported).
     ///
     /// # Examples
     ///
     /// ```
-    /// # use failure::Error;
     /// # use ht16k33::i2c_mock::I2cMock;
     /// # use ht16k33::HT16K33;
-    /// # fn main() -> Result<(), Box<Error>> {
+    /// # fn main() {
     /// # let mut i2c = I2cMock::new();
     /// # let address = 0u8;
     ///
     /// let mut ht16k33 = HT16K33::new(i2c, address);
-    /// ht16k33.write_display_buffer();
+    /// assert!(ht16k33.is_synced());
     ///
-    /// # Ok(())
     /// # }
     /// ```
-    pub fn write_display_buffer(&mut self) -> Result<(), E> {
-        let mut write_buffer = [0u8; ROWS_SIZE + 1];
-        write_buffer[0] = DisplayDataAddress::ROW_0.bits();
-
-        for value in 0usize..self.buffer.len() {
-            write_buffer[value + 1] = self.buffer[value].bits();
-        }
-
-        self.i2c.write(self.address, &write_buffer)?;
-
-        Ok(())
+    pub fn is_synced(&self) -> bool {
+        *self.state.oscillator() == self.oscillator_confirmed
+            && *self.state.display() == self.display_confirmed
+            && (!self.dimming_supported || *self.state.dimming() == self.dimming_confirmed)
     }
 
-    /// Read the display buffer from the HT16K33 chip.
+    /// Enable/disable an LED address in the display buffer.
     ///
-    /// # Examples
+    /// The buffer must be written using [write_display_buffer()](struct.HT16K33.html#method.write_display_buffer)
+    /// for the change to be displayed.
     ///
-    /// ```
+    /// # Arguments
+    ///
+    /// * `location` - The LED location to update.
+    /// * `enabled` - Set the LED on (true) or off (false).
+    ///
+    /// # Examples
+    ///
+    /// ```
     /// # use ht16k33::i2c_mock::I2cMock;
     /// # use ht16k33::HT16K33;
-    /// # use std::error::Error;
-    /// # fn main() -> Result<(), Box<Error>> {
+    /// # use ht16k33::ValidationError;
+    /// use ht16k33::LedLocation;
+    /// # fn main() -> Result<(), ValidationError> {
     /// # let mut i2c = I2cMock::new();
     /// # let address = 0u8;
     ///
     /// let mut ht16k33 = HT16K33::new(i2c, address);
-    /// ht16k33.read_display_buffer();
+    ///
+    /// let led_location = LedLocation::new(0, 0)?;
+    /// ht16k33.update_display_buffer(led_location, true);
     ///
     /// # Ok(())
     /// # }
     /// ```
-    pub fn read_display_buffer(&mut self) -> Result<(), E> {
-        let mut read_buffer = [0u8; ROWS_SIZE];
+    pub fn update_display_buffer(&mut self, location: LedLocation, enabled: bool) {
+        // TODO Validate `address` parameter.
 
-        self.i2c.write_read(
-            self.address,
-            &[DisplayDataAddress::ROW_0.bits()],
-            &mut read_buffer,
-        )?;
+        // Turn on/off the specified LED.
+        self.state.update_display_buffer(location, enabled);
+        self.dirty[location.row_as_index()] = true;
+    }
+
+    /// Clear contents of the display buffer.
+    ///
+    /// The buffer must be written using [write_display_buffer()](struct.HT16K33.html#method.write_display_buffer)
+    /// for the change to be displayed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # fn main() {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// ht16k33.clear_display_buffer();
+    ///
+    /// # }
+    /// ```
+    pub fn clear_display_buffer(&mut self) {
+        self.state.clear_display_buffer();
+        self.dirty = [true; ROWS_SIZE];
+    }
+
+    /// Turn all the LEDs in a region of the display buffer on or off.
+    ///
+    /// The buffer must be written using [write_display_buffer()](struct.HT16K33.html#method.write_display_buffer)
+    /// for the change to be displayed.
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - The region to fill.
+    /// * `enabled` - Set the LEDs on (true) or off (false).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # use ht16k33::ValidationError;
+    /// use ht16k33::{DisplayData, Region};
+    /// # fn main() -> Result<(), ValidationError> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    ///
+    /// // Light a 2x2 square in the top-left corner.
+    /// ht16k33.fill_region(Region::new(0, 2, 0, 2)?, true);
+    ///
+    /// assert_eq!(DisplayData::COMMON_0 | DisplayData::COMMON_1, ht16k33.display_buffer()[1]);
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn fill_region(&mut self, region: Region, enabled: bool) {
+        let commons = region.commons();
+        let rows = region.row_start() as usize..region.row_end() as usize;
+
+        for row in self.state.display_buffer_mut()[rows.clone()].iter_mut() {
+            row.set(commons, enabled);
+        }
+
+        for dirty in self.dirty[rows].iter_mut() {
+            *dirty = true;
+        }
+    }
+
+    /// Return the current canary location, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # fn main() {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// let canary = ht16k33.canary();
+    ///
+    /// # }
+    /// ```
+    pub fn canary(&self) -> Option<LedLocation> {
+        self.canary
+    }
+
+    /// Reserve an unused LED location in the display buffer as a canary for detecting chip resets.
+    ///
+    /// The display setup registers are write-only, so the driver cannot read back whether the chip has been
+    /// reset and lost its configuration. Instead, the canary LED is added to every write of its ROW to the chip,
+    /// whatever the display buffer, overlay, or effect holds, and
+    /// [validate_state()](struct.HT16K33.html#method.validate_state) reads it back from the chip.
+    ///
+    /// The canary is not stored in the display buffer, so it cannot be cleared by drawing, and is removed from
+    /// the display buffer by [read_display_buffer()](struct.HT16K33.html#method.read_display_buffer).
+    ///
+    /// The location must not be wired to a real LED, it will be lit whenever the display is on. Passing `None`
+    /// releases the previous canary location.
+    ///
+    /// The buffer must be written using [write_display_buffer()](struct.HT16K33.html#method.write_display_buffer)
+    /// for the change to be stored on the chip.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - The unused LED location to reserve, or `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # use ht16k33::ValidationError;
+    /// use ht16k33::LedLocation;
+    /// # fn main() -> Result<(), ValidationError> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    ///
+    /// ht16k33.set_canary(Some(LedLocation::new(15, 7)?));
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_canary(&mut self, location: Option<LedLocation>) {
+        // Both the released and the reserved location need to be written.
+        for canary in [self.canary, location].iter().flatten() {
+            self.dirty[canary.row_as_index()] = true;
+        }
+
+        self.canary = location;
+    }
+
+    /// Return whether the chip supports the dimming command, see [set_dimming_supported()](struct.HT16K33.html#method.set_dimming_supported).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # fn main() {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// assert!(ht16k33.dimming_supported());
+    ///
+    /// # }
+    /// ```
+    pub fn dimming_supported(&self) -> bool {
+        self.dimming_supported
+    }
+
+    /// Return whether the last dimming change was ignored because dimming is not supported.
+    ///
+    /// [set_dimming()](struct.HT16K33.html#method.set_dimming) returns `Ok` without writing anything when
+    /// dimming is marked as unsupported, check this after it to know whether the brightness actually changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::Dimming;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    ///
+    /// ht16k33.set_dimming_supported(false);
+    /// ht16k33.set_dimming(Dimming::BRIGHTNESS_MIN)?;
+    /// assert!(ht16k33.dimming_ignored());
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn dimming_ignored(&self) -> bool {
+        self.dimming_ignored
+    }
+
+    /// Mark whether the chip supports the dimming command.
+    ///
+    /// Some clone chips reject the dimming command while display writes still succeed. Once dimming is marked
+    /// as unsupported, [set_dimming()](struct.HT16K33.html#method.set_dimming) is a no-op that returns `Ok`,
+    /// including when called by the initialization and alert methods, so the display keeps working at the
+    /// chip's current brightness. Use [dimming_ignored()](struct.HT16K33.html#method.dimming_ignored) to
+    /// tell whether a call was ignored.
+    ///
+    /// The [dimming()](struct.HT16K33.html#method.dimming) state is not updated by the no-op calls, so it
+    /// reports the last brightness that was actually written.
+    ///
+    /// # Arguments
+    ///
+    /// * `supported` - Whether the dimming command is sent to the chip.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::Dimming;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    ///
+    /// if ht16k33.set_dimming(Dimming::BRIGHTNESS_MIN).is_err() {
+    ///     ht16k33.set_dimming_supported(false);
+    /// }
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_dimming_supported(&mut self, supported: bool) {
+        self.dimming_supported = supported;
+    }
+
+    /// Return whether software flashing is limited to safe flash rates, see [set_flash_guard()](struct.HT16K33.html#method.set_flash_guard).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # fn main() {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// assert!(!ht16k33.flash_guard());
+    ///
+    /// # }
+    /// ```
+    pub fn flash_guard(&self) -> bool {
+        self.flash_guard
+    }
+
+    /// Limit software flashing of the whole display to safe flash rates, for photosensitive viewers.
+    ///
+    /// When enabled, [flash_alert()](struct.HT16K33.html#method.flash_alert) slows flash rates between 3 Hz and
+    /// 60 Hz down to [`SAFE_FLASH_PERIOD_MS`], keeping the on/off ratio. Use [`validate_flash_rate`] to reject
+    /// unsafe rates with an error instead. Disabled by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to limit the flash rate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # use embedded_hal_mock::delay::MockNoop as Delay;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    /// # let mut delay = Delay::new();
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// ht16k33.set_flash_guard(true);
+    ///
+    /// // Flashes at ~3 Hz instead of 10 Hz.
+    /// ht16k33.flash_alert(3, 50, 50, &mut delay)?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`SAFE_FLASH_PERIOD_MS`]: constant.SAFE_FLASH_PERIOD_MS.html
+    /// [`validate_flash_rate`]: fn.validate_flash_rate.html
+    pub fn set_flash_guard(&mut self, enabled: bool) {
+        self.flash_guard = enabled;
+    }
+
+    /// Return the maximum length of a single I2C write.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # fn main() {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// let max_write_len = ht16k33.max_write_len();
+    ///
+    /// # }
+    /// ```
+    pub fn max_write_len(&self) -> usize {
+        self.max_write_len
+    }
+
+    /// Limit the length of a single I2C write, for HALs that cap the size of writes.
+    ///
+    /// Display buffer writes longer than this are split into multiple transactions, each starting with the
+    /// address of its first row. The default is `ROWS_SIZE + 1`, enough to write the whole display buffer in a
+    /// single transaction.
+    ///
+    /// The length includes the address byte, so values less than `2` are treated as `2`, the shortest write that
+    /// carries any data.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_write_len` - The maximum number of bytes in a single I2C write.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    ///
+    /// // Writes the display buffer as 4 transactions of 4 rows each.
+    /// ht16k33.set_max_write_len(5);
+    /// ht16k33.write_display_buffer()?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_max_write_len(&mut self, max_write_len: usize) {
+        self.max_write_len = core::cmp::max(max_write_len, 2);
+    }
+
+    /// Return the order of the COMMON bits in each ROW of display RAM.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::BitOrder;
+    /// # fn main() {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// assert_eq!(BitOrder::Normal, ht16k33.bit_order());
+    ///
+    /// # }
+    /// ```
+    pub fn bit_order(&self) -> BitOrder {
+        self.state.bit_order()
+    }
+
+    /// Set the order of the COMMON bits in each ROW of display RAM, for boards that wire the COMMONs in reverse.
+    ///
+    /// The bit order is applied when writing to and reading from the chip, so the display buffer and every
+    /// `LedLocation` keep the logical order. The display buffer must be written again for the change to be
+    /// displayed.
+    ///
+    /// # Arguments
+    ///
+    /// * `bit_order` - The order of the COMMON bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::{BitOrder, LedLocation};
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// ht16k33.set_bit_order(BitOrder::Reversed);
+    ///
+    /// // Written to COMMON 7 of ROW 0 in display RAM.
+    /// ht16k33.set_led(LedLocation::new(0, 0)?, true)?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_bit_order(&mut self, bit_order: BitOrder) {
+        self.state.set_bit_order(bit_order);
+        self.dirty = [true; ROWS_SIZE];
+    }
+
+    /// Return the display RAM row that each display buffer row is written to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::DisplayDataAddress;
+    /// # fn main() {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// assert_eq!(DisplayDataAddress::ROW_3, ht16k33.row_map()[3]);
+    ///
+    /// # }
+    /// ```
+    pub fn row_map(&self) -> &[DisplayDataAddress; ROWS_SIZE] {
+        self.state.row_map()
+    }
+
+    /// Set the display RAM row that each display buffer row is written to, for boards that wire the ROWs out of
+    /// order.
+    ///
+    /// The map is applied when writing to and reading from the chip, so the display buffer and every
+    /// `LedLocation` keep the logical order. The display buffer must be written again for the change to be
+    /// displayed.
+    ///
+    /// # Arguments
+    ///
+    /// * `row_map` - The display RAM row for each display buffer row, each row must be used exactly once.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ValidationError::DuplicateValue`] if a display RAM row is used more than once, the row map is
+    /// not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::{DisplayDataAddress, ROWS_SIZE};
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    ///
+    /// // The board wires the ROWs in reverse.
+    /// let mut row_map = [DisplayDataAddress::ROW_0; ROWS_SIZE];
+    /// for (row, ram_row) in row_map.iter_mut().enumerate() {
+    ///     *ram_row = DisplayDataAddress::from_bits_truncate((ROWS_SIZE - 1 - row) as u8);
+    /// }
+    ///
+    /// ht16k33.set_row_map(row_map)?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ValidationError::DuplicateValue`]: enum.ValidationError.html#variant.DuplicateValue
+    pub fn set_row_map(
+        &mut self,
+        row_map: [DisplayDataAddress; ROWS_SIZE],
+    ) -> Result<(), ValidationError> {
+        self.state.set_row_map(row_map)?;
+        self.dirty = [true; ROWS_SIZE];
+
+        Ok(())
+    }
+
+    /// Return the current overlay layer, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # fn main() {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// let overlay = ht16k33.overlay();
+    ///
+    /// # }
+    /// ```
+    pub fn overlay(&self) -> Option<&[DisplayData; ROWS_SIZE]> {
+        self.overlay.as_ref()
+    }
+
+    /// Show an overlay layer, e.g. an alert, instead of the display buffer for `timeout_ticks` ticks.
+    ///
+    /// The display buffer is the base layer for the normal content, and keeps being updated as usual while the
+    /// overlay is shown. The overlay is written by [tick()](struct.HT16K33.html#method.tick), and once it expires the
+    /// display buffer is automatically restored. Setting a new overlay replaces the current one.
+    ///
+    /// The [canary](struct.HT16K33.html#method.set_canary), if set, is written with the overlay as well.
+    ///
+    /// *NOTE: While an overlay is shown, use `tick()` to update the chip; writing the display buffer directly
+    /// replaces the overlay on the chip until the next `tick()`.*
+    ///
+    /// # Arguments
+    ///
+    /// * `overlay` - The overlay layer to show.
+    /// * `timeout_ticks` - The number of ticks to show the overlay for.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::{DisplayData, ROWS_SIZE};
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    ///
+    /// // Light all the LEDs for 10 ticks.
+    /// ht16k33.set_overlay([DisplayData::all(); ROWS_SIZE], 10);
+    ///
+    /// loop {
+    ///     ht16k33.tick()?;
+    ///     # break;
+    /// }
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_overlay(&mut self, overlay: [DisplayData; ROWS_SIZE], timeout_ticks: u32) {
+        self.overlay = Some(overlay);
+        self.overlay_ticks = timeout_ticks;
+    }
+
+    /// Remove the overlay layer, the display buffer is restored by the next [tick()](struct.HT16K33.html#method.tick).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # fn main() {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// ht16k33.clear_overlay();
+    ///
+    /// # }
+    /// ```
+    pub fn clear_overlay(&mut self) {
+        self.overlay = None;
+        self.overlay_ticks = 0;
+    }
+}
+
+impl<I2C, E> HT16K33<I2C>
+where
+    I2C: Write<Error = E> + WriteRead<Error = E>,
+{
+    /// Initialize the HT16K33.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// ht16k33.initialize()?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn initialize(&mut self) -> Result<(), E> {
+        // Enable the oscillator so we can use the device.
+        self.set_oscillator(Oscillator::ON)?;
+
+        // Set all values to match their defaults.
+        self.set_display(DeviceDefaults::POWER_ON.display)?;
+        self.set_dimming(DeviceDefaults::POWER_ON.dimming)?;
+
+        // And clear the display.
+        self.clear_display_buffer();
+        self.write_display_buffer()?;
+
+        Ok(())
+    }
+
+    /// Initialize the HT16K33 with the given options.
+    ///
+    /// The display buffer is cleared and written before the display is turned on. If the options have a `ramp`
+    /// then the brightness starts at [`BRIGHTNESS_MIN`] and is stepped up evenly to the requested brightness over
+    /// the ramp duration, instead of snapping straight to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The initial brightness, display state, and optional brightness ramp.
+    /// * `delay` - The delay provider, only used for the brightness ramp.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # use embedded_hal_mock::delay::MockNoop as Delay;
+    /// use ht16k33::{Dimming, Display, InitOptions, RampMs};
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    /// # let mut delay = Delay::new();
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    ///
+    /// let options = InitOptions {
+    ///     brightness: Dimming::BRIGHTNESS_MAX,
+    ///     display: Display::ON,
+    ///     ramp: Some(RampMs(1500)),
+    /// };
+    /// ht16k33.initialize_with(options, &mut delay)?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`BRIGHTNESS_MIN`]: struct.Dimming.html#associatedconstant.BRIGHTNESS_MIN
+    pub fn initialize_with<D>(&mut self, options: InitOptions, delay: &mut D) -> Result<(), E>
+    where
+        D: DelayMs<u16>,
+    {
+        // Enable the oscillator so we can use the device.
+        self.set_oscillator(Oscillator::ON)?;
+
+        let brightness = match options.ramp {
+            Some(_) => Dimming::BRIGHTNESS_MIN,
+            None => options.brightness,
+        };
+        self.set_dimming(brightness)?;
+
+        // Clear the display before turning it on.
+        self.clear_display_buffer();
+        self.write_display_buffer()?;
+        self.set_display(options.display)?;
+
+        if let Some(RampMs(ramp)) = options.ramp {
+            let steps = options.brightness.bits() - Dimming::BRIGHTNESS_MIN.bits();
+
+            for step in 1..=steps {
+                delay.delay_ms(ramp / u16::from(steps));
+                self.set_dimming(Dimming::from_bits_truncate(
+                    Dimming::BRIGHTNESS_MIN.bits() + step,
+                ))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write the oscillator, display, and dimming states that have not been written successfully to the chip.
+    ///
+    /// Use this to retry after a failed write, states that are already synced are not written again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::Display;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    ///
+    /// if ht16k33.set_display(Display::ON).is_err() {
+    ///     // Try again later.
+    ///     ht16k33.sync()?;
+    /// }
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn sync(&mut self) -> Result<(), E> {
+        // The oscillator must be running before the display is enabled.
+        if *self.state.oscillator() != self.oscillator_confirmed {
+            self.set_oscillator(*self.state.oscillator())?;
+        }
+
+        if self.dimming_supported && *self.state.dimming() != self.dimming_confirmed {
+            self.set_dimming(*self.state.dimming())?;
+        }
+
+        if *self.state.display() != self.display_confirmed {
+            self.set_display(*self.state.display())?;
+        }
+
+        Ok(())
+    }
+
+    /// Check that the chip still holds the canary, as a best-effort detection of chip resets.
+    ///
+    /// Returns `false` if the canary LED is no longer enabled in the chip's display RAM, meaning the chip has
+    /// most likely been reset and should be re-initialized. Always returns `true` if no canary is set.
+    ///
+    /// *NOTE: Display RAM is undefined after a Power-on Reset, so a reset chip may still hold the canary by
+    /// chance.*
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::LedLocation;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    ///
+    /// ht16k33.set_canary(Some(LedLocation::new(15, 7)?));
+    /// ht16k33.write_display_buffer()?;
+    ///
+    /// if !ht16k33.validate_state()? {
+    ///     ht16k33.initialize()?;
+    /// }
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn validate_state(&mut self) -> Result<bool, E> {
+        let canary = match self.canary {
+            Some(canary) => canary,
+            None => return Ok(true),
+        };
+
+        let mut read_buffer = [0u8; 1];
+
+        self.i2c.write_read(
+            self.address,
+            &[self.state.row_map()[canary.row_as_index()].bits()],
+            &mut read_buffer,
+        )?;
+
+        Ok(self
+            .state
+            .bit_order()
+            .apply(DisplayData::from_bits_truncate(read_buffer[0]))
+            .contains(canary.common))
+    }
+
+    /// Control the oscillator.
+    ///
+    /// # Arguments
+    ///
+    /// * `oscillator` - Set the oscillator On/Off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::Oscillator;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// ht16k33.set_oscillator(Oscillator::ON)?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_oscillator(&mut self, oscillator: Oscillator) -> Result<(), E> {
+        self.state.set_oscillator(oscillator);
+
+        Self::write_register_to(
+            &mut self.i2c,
+            self.address,
+            self.state.system_setup_bytes(),
+            &mut self.oscillator_confirmed,
+            oscillator,
+        )
+    }
+
+    /// Control the display.
+    ///
+    /// # Arguments
+    ///
+    /// * `display` - Set the display On/Off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::Display;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// ht16k33.set_display(Display::HALF_HZ)?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_display(&mut self, display: Display) -> Result<(), E> {
+        if display.contains(Display::ON) {
+            self.wake()?;
+        }
+
+        self.state.set_display(display);
+
+        Self::write_register_to(
+            &mut self.i2c,
+            self.address,
+            self.state.display_setup_bytes(),
+            &mut self.display_confirmed,
+            display,
+        )
+    }
+
+    /// Control the display dimming.
+    ///
+    /// This is a no-op if dimming is not supported, see [set_dimming_supported()](struct.HT16K33.html#method.set_dimming_supported).
+    /// A skipped change still returns `Ok`, and is reported by [dimming_ignored()](struct.HT16K33.html#method.dimming_ignored).
+    ///
+    /// # Arguments
+    ///
+    /// * `dimming` - Set the dimming brightness.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::Dimming;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// ht16k33.set_dimming(Dimming::from_u8(4)?)?;
+    /// assert!(!ht16k33.dimming_ignored());
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_dimming(&mut self, dimming: Dimming) -> Result<(), E> {
+        if !self.update_dimming(dimming) {
+            return Ok(());
+        }
+
+        Self::write_register_to(
+            &mut self.i2c,
+            self.address,
+            self.state.dimming_set_bytes(),
+            &mut self.dimming_confirmed,
+            dimming,
+        )
+    }
+
+    /// Send the factory test-mode command (`0xD9`) to the chip.
+    ///
+    /// **WARNING: The test mode is undocumented by the datasheet. The chip's behavior in test mode is unknown,
+    /// and it may not respond to other commands until it is power cycled. Only use this for characterizing
+    /// chips, never in production firmware.**
+    ///
+    /// *Requires the `testmode` feature.*
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// ht16k33.enter_test_mode()?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "testmode")]
+    pub fn enter_test_mode(&mut self) -> Result<(), E> {
+        self.i2c.write(self.address, &[TEST_MODE_COMMAND])?;
+
+        Ok(())
+    }
+
+    /// Control an LED.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - The LED location to update.
+    /// * `enabled` - Set the LED on (true) or off (false).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::LedLocation;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    ///
+    /// let led_location = LedLocation::new(0, 0)?;
+    /// ht16k33.set_led(led_location, true)?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_led(&mut self, location: LedLocation, enabled: bool) -> Result<(), E> {
+        // TODO Validate `address` parameter.
+        self.update_display_buffer(location, enabled);
+        self.wake()?;
+
+        // Only this row is written, other pending changes still need to be flushed.
+        let mut rows = [false; ROWS_SIZE];
+        rows[location.row_as_index()] = true;
+
+        Self::write_buffer_rows_to(
+            &mut self.i2c,
+            self.address,
+            self.max_write_len,
+            &self.state,
+            self.canary,
+            &mut self.dirty,
+            &rows,
+        )
+    }
+
+    /// Control several LEDs, with a single write.
+    ///
+    /// The display buffer is updated for all the LEDs, and then the ROWs from the lowest to the highest changed
+    /// ROW are written in a single auto-incrementing transaction, unless the [row_map()] splits them or the
+    /// [max_write_len()] is too small for them. Pending changes in the ROWs in between are written too.
+    ///
+    /// # Arguments
+    ///
+    /// * `leds` - The (LED location, enabled) pairs to update, if a location is given more than once then the
+    ///   last value is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::LedLocation;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    ///
+    /// // Written as a single transaction for ROWs 0-2.
+    /// ht16k33.set_leds([
+    ///     (LedLocation::new(0, 0)?, true),
+    ///     (LedLocation::new(0, 1)?, false),
+    ///     (LedLocation::new(2, 7)?, true),
+    /// ])?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [row_map()]: struct.HT16K33.html#method.row_map
+    /// [max_write_len()]: struct.HT16K33.html#method.max_write_len
+    pub fn set_leds<L>(&mut self, leds: L) -> Result<(), E>
+    where
+        L: IntoIterator<Item = (LedLocation, bool)>,
+    {
+        let mut first = ROWS_SIZE;
+        let mut last = 0;
+
+        for (location, enabled) in leds {
+            self.update_display_buffer(location, enabled);
+
+            first = first.min(location.row_as_index());
+            last = last.max(location.row_as_index());
+        }
+
+        if first > last {
+            return Ok(());
+        }
+
+        let mut rows = [false; ROWS_SIZE];
+        for row in rows[first..=last].iter_mut() {
+            *row = true;
+        }
+
+        let buffer = *self.state.display_buffer();
+        self.write_rows(&buffer, &rows)?;
+
+        // Only these rows are written, other pending changes still need to be flushed.
+        for dirty in self.dirty[first..=last].iter_mut() {
+            *dirty = false;
+        }
+
+        Ok(())
+    }
+
+    /// Update the given rows of the display buffer and write them to the HT16K33 chip.
+    ///
+    /// The rows are sorted and contiguous addresses are merged, so each run of contiguous rows is written in a
+    /// single auto-incrementing transaction. If an address is given more than once then the last value is used.
+    ///
+    /// The transactions are written in ascending display RAM address order, so they only depend on which rows
+    /// are written and their final values, never on the order of `commons`.
+    ///
+    /// # Arguments
+    ///
+    /// * `commons` - The (row address, common data) pairs to write.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::{DisplayData, DisplayDataAddress};
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    ///
+    /// // Written as two transactions, for rows 1-2 and row 7.
+    /// ht16k33.write_commons(&[
+    ///     (DisplayDataAddress::ROW_2, DisplayData::COMMON_0),
+    ///     (DisplayDataAddress::ROW_7, DisplayData::all()),
+    ///     (DisplayDataAddress::ROW_1, DisplayData::COMMON_3),
+    /// ])?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_commons(
+        &mut self,
+        commons: &[(DisplayDataAddress, DisplayData)],
+    ) -> Result<(), E> {
+        let mut pending = [false; ROWS_SIZE];
+
+        for &(address, data) in commons.iter() {
+            let row = address.bits() as usize;
+
+            self.state.display_buffer_mut()[row] = data;
+            pending[row] = true;
+        }
+
+        for (dirty, &pending) in self.dirty.iter_mut().zip(pending.iter()) {
+            *dirty |= pending;
+        }
+
+        let buffer = *self.state.display_buffer();
+        self.write_rows(&buffer, &pending)?;
+
+        // Only these rows are written, other pending changes still need to be flushed.
+        for (dirty, &pending) in self.dirty.iter_mut().zip(pending.iter()) {
+            *dirty &= !pending;
+        }
+
+        Ok(())
+    }
+
+    /// Flash all the LEDs on and off to signal an alert, then restore the display.
+    ///
+    /// The display buffer, display state and dimming are saved, all LEDs are flashed on/off `times` times with the
+    /// display on at full brightness, and then the saved display buffer, display state and dimming are written back
+    /// to the chip. If writing to the chip fails then the alert stops early and the display is not restored.
+    ///
+    /// If the [flash_guard()](struct.HT16K33.html#method.flash_guard) is enabled, flash rates between 3 Hz and
+    /// 60 Hz are slowed down to [`SAFE_FLASH_PERIOD_MS`].
+    ///
+    /// # Arguments
+    ///
+    /// * `times` - The number of times to flash the LEDs.
+    /// * `on_ms` - How long the LEDs are on for each flash, in milliseconds.
+    /// * `off_ms` - How long the LEDs are off for each flash, in milliseconds.
+    /// * `delay` - The delay provider.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # use embedded_hal_mock::delay::MockNoop as Delay;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    /// # let mut delay = Delay::new();
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    ///
+    /// // Flash 3 times, 200ms on and 100ms off.
+    /// ht16k33.flash_alert(3, 200, 100, &mut delay)?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`SAFE_FLASH_PERIOD_MS`]: constant.SAFE_FLASH_PERIOD_MS.html
+    pub fn flash_alert<D>(
+        &mut self,
+        times: u8,
+        on_ms: u16,
+        off_ms: u16,
+        delay: &mut D,
+    ) -> Result<(), E>
+    where
+        D: DelayMs<u16>,
+    {
+        let buffer = *self.state.display_buffer();
+        let display = *self.state.display();
+        let dimming = *self.state.dimming();
+
+        let (on_ms, off_ms) = if self.flash_guard {
+            flash::limit_flash_rate(on_ms, off_ms)
+        } else {
+            (on_ms, off_ms)
+        };
+
+        self.set_display(Display::ON)?;
+
+        if dimming != Dimming::BRIGHTNESS_MAX {
+            self.set_dimming(Dimming::BRIGHTNESS_MAX)?;
+        }
+
+        for _ in 0..times {
+            *self.state.display_buffer_mut() = [DisplayData::all(); ROWS_SIZE];
+            self.write_display_buffer()?;
+            delay.delay_ms(on_ms);
+
+            self.clear_display_buffer();
+            self.write_display_buffer()?;
+            delay.delay_ms(off_ms);
+        }
+
+        *self.state.display_buffer_mut() = buffer;
+        self.write_display_buffer()?;
+        self.set_display(display)?;
+
+        if dimming != Dimming::BRIGHTNESS_MAX {
+            self.set_dimming(dimming)?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the display buffer to the HT16K33 chip.
+    ///
+    /// The transactions are written in ascending display RAM address order, so they only depend on the display
+    /// buffer contents, never on the order it was updated in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// ht16k33.write_display_buffer();
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_display_buffer(&mut self) -> Result<(), E> {
+        self.wake()?;
+
+        Self::write_buffer_rows_to(
+            &mut self.i2c,
+            self.address,
+            self.max_write_len,
+            &self.state,
+            self.canary,
+            &mut self.dirty,
+            &[true; ROWS_SIZE],
+        )
+    }
+
+    /// Write only the display buffer rows that changed since they were last written to the HT16K33 chip.
+    ///
+    /// Each run of contiguous dirty rows is written in a single auto-incrementing transaction, in ascending
+    /// display RAM address order. Nothing is written if no rows are dirty, see
+    /// [dirty_rows()](struct.HT16K33.html#method.dirty_rows).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::LedLocation;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// ht16k33.write_display_buffer()?;
+    ///
+    /// // Only ROW 3 is written.
+    /// ht16k33.update_display_buffer(LedLocation::new(3, 0)?, true);
+    /// ht16k33.write_dirty()?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_dirty(&mut self) -> Result<(), E> {
+        self.wake()?;
+
+        let rows = self.dirty;
+        Self::write_buffer_rows_to(
+            &mut self.i2c,
+            self.address,
+            self.max_write_len,
+            &self.state,
+            self.canary,
+            &mut self.dirty,
+            &rows,
+        )
+    }
+
+    /// Write the display buffer to the HT16K33 chip, transformed by an [`Effect`].
+    ///
+    /// The effect is applied to a copy of the display buffer, so the display buffer itself is unchanged and is
+    /// still considered written afterwards.
+    ///
+    /// # Arguments
+    ///
+    /// * `effect` - The effect, or chain of effects, to apply.
+    /// * `delta_ms` - The time since the effect was last applied, in milliseconds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::{Effect, Invert, Scanline};
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// let mut effect = Scanline::new(100).then(Invert);
+    ///
+    /// for _ in 0..10 {
+    ///     // Draw into the display buffer as usual, and then:
+    ///     ht16k33.write_display_buffer_effect(&mut effect, 33)?;
+    /// }
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Effect`]: trait.Effect.html
+    pub fn write_display_buffer_effect<F>(&mut self, effect: &mut F, delta_ms: u32) -> Result<(), E>
+    where
+        F: Effect,
+    {
+        self.dirty = [true; ROWS_SIZE];
+
+        let mut frame = *self.state.display_buffer();
+        effect.apply(&mut frame, delta_ms);
+        self.write_rows(&frame, &[true; ROWS_SIZE])?;
+
+        self.dirty = [false; ROWS_SIZE];
+
+        Ok(())
+    }
+
+    // Turn the oscillator on if auto-wake is enabled and the chip is in standby.
+    fn wake(&mut self) -> Result<(), E> {
+        if self.auto_wake && self.power_state() == PowerState::Standby {
+            self.set_oscillator(Oscillator::ON)?;
+        }
+
+        Ok(())
+    }
+
+    // Write the selected buffer rows with the owned I2C device, see `write_rows_to()`.
+    fn write_rows(
+        &mut self,
+        buffer: &[DisplayData; ROWS_SIZE],
+        rows: &[bool; ROWS_SIZE],
+    ) -> Result<(), E> {
+        self.wake()?;
+
+        Self::write_rows_to(
+            &mut self.i2c,
+            self.address,
+            self.max_write_len,
+            &self.state,
+            self.canary,
+            buffer,
+            rows,
+        )
+    }
+
+    /// Compose the layers and write them to the HT16K33 chip.
+    ///
+    /// While an overlay is shown it is written to the chip, and each tick counts down its timeout. Otherwise, the
+    /// display buffer is written if it [needs_flush()](struct.HT16K33.html#method.needs_flush), which includes
+    /// restoring it after an overlay expires.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// ht16k33.tick()?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn tick(&mut self) -> Result<(), E> {
+        if self.overlay_ticks == 0 {
+            self.overlay = None;
+        }
+
+        if let Some(overlay) = self.overlay {
+            self.overlay_ticks -= 1;
+
+            // The chip no longer shows the display buffer.
+            self.dirty = [true; ROWS_SIZE];
+
+            return self.write_rows(&overlay, &[true; ROWS_SIZE]);
+        }
+
+        if self.needs_flush() {
+            self.write_dirty()?;
+        }
+
+        Ok(())
+    }
+
+    /// Service the display and keyscan together: write the display buffer, then read the key data RAM.
+    ///
+    /// The display RAM and key data RAM share the chip's address pointer. Each transaction sets the address
+    /// pointer explicitly, and the display write always completes before the key read starts, so display writes
+    /// never corrupt the key data that is read back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// ht16k33.service()?;
+    ///
+    /// let &key_buffer = ht16k33.key_buffer();
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn service(&mut self) -> Result<(), E> {
+        self.write_display_buffer()?;
+        self.read_key_buffer()?;
+
+        Ok(())
+    }
+
+    /// Check whether the keys have changed since the last poll, reading the INT flag at most once every
+    /// `min_interval_ticks` polls.
+    ///
+    /// Each call is one tick; calls in between reads return `false` without touching the I2C bus, so this can
+    /// be called from a tight loop without hammering the bus. The first call always reads the INT flag.
+    ///
+    /// If the INT flag is set then the key data RAM is read into the [key_buffer()](struct.HT16K33.html#method.key_buffer),
+    /// which also clears the INT flag so that stale flags are not reported by the next poll.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_interval_ticks` - The minimum number of polls between INT flag reads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    ///
+    /// if ht16k33.keys_changed_since_last_poll(10)? {
+    ///     let &key_buffer = ht16k33.key_buffer();
+    /// }
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn keys_changed_since_last_poll(&mut self, min_interval_ticks: u32) -> Result<bool, E> {
+        self.poll_ticks = self.poll_ticks.saturating_add(1);
+
+        if self.poll_ticks < min_interval_ticks {
+            return Ok(false);
+        }
+
+        self.poll_ticks = 0;
+
+        let mut int_flag = [0u8; 1];
+
+        self.i2c
+            .write_read(self.address, &[INT_FLAG_ADDRESS], &mut int_flag)?;
+
+        if int_flag[0] == 0 {
+            return Ok(false);
+        }
+
+        // Reading the key data RAM clears the INT flag.
+        self.read_key_buffer()?;
+
+        Ok(true)
+    }
+
+    /// Blank the display until a key is pressed, then restore it.
+    ///
+    /// Keyscan needs the oscillator, so the chip cannot be put in standby; instead the display is turned off,
+    /// which stops driving the LEDs, and the INT flag is polled every `poll_ms` until a key is pressed. The
+    /// pressed keys are then in the [key_buffer()](struct.HT16K33.html#method.key_buffer), and the oscillator
+    /// and display states are restored.
+    ///
+    /// If writing to the chip fails then the display is not restored.
+    ///
+    /// # Arguments
+    ///
+    /// * `poll_ms` - How long to wait between INT flag reads, in milliseconds.
+    /// * `delay` - The delay provider.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # use embedded_hal_mock::delay::MockNoop as Delay;
+    /// use ht16k33::Display;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # i2c.oscillator = ht16k33::Oscillator::ON;
+    /// # i2c.scan_keys([0, 0, 0, 0, 0, 1]);
+    /// # let address = 0u8;
+    /// # let mut delay = Delay::new();
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// ht16k33.initialize()?;
+    /// ht16k33.set_display(Display::ON)?;
+    ///
+    /// // Turn off the LEDs while idle.
+    /// ht16k33.blank_until_key(50, &mut delay)?;
+    ///
+    /// let &key_buffer = ht16k33.key_buffer();
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn blank_until_key<D>(&mut self, poll_ms: u16, delay: &mut D) -> Result<(), E>
+    where
+        D: DelayMs<u16>,
+    {
+        let oscillator = *self.state.oscillator();
+        let display = *self.state.display();
+
+        self.set_oscillator(Oscillator::ON)?;
+        self.set_display(Display::OFF)?;
+
+        while !self.keys_changed_since_last_poll(0)? {
+            delay.delay_ms(poll_ms);
+        }
+
+        self.set_display(display)?;
+        self.set_oscillator(oscillator)?;
+
+        Ok(())
+    }
+
+    /// Read the key data RAM from the HT16K33 chip into the [key_buffer()](struct.HT16K33.html#method.key_buffer).
+    ///
+    /// All [`KEY_DATA_SIZE`] bytes are read in a single transaction starting at the first key data address, as
+    /// required by the datasheet. This also clears the INT flag on the chip.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// ht16k33.read_key_buffer()?;
+    ///
+    /// let &key_buffer = ht16k33.key_buffer();
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`KEY_DATA_SIZE`]: constant.KEY_DATA_SIZE.html
+    pub fn read_key_buffer(&mut self) -> Result<(), E> {
+        self.wake()?;
+
+        self.i2c
+            .write_read(self.address, &[KEY_DATA_ADDRESS], &mut self.key_buffer)?;
+
+        Ok(())
+    }
+
+    /// Read the key data RAM from the HT16K33 chip, and return it as [`KeyData`].
+    ///
+    /// This also updates the [key_buffer()](struct.HT16K33.html#method.key_buffer) and clears the INT flag on the
+    /// chip, see [read_key_buffer()](struct.HT16K33.html#method.read_key_buffer).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    ///
+    /// if ht16k33.read_key_data()?.any_pressed() {
+    ///     // Handle the key press.
+    /// }
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`KeyData`]: struct.KeyData.html
+    pub fn read_key_data(&mut self) -> Result<KeyData, E> {
+        self.read_key_buffer()?;
+
+        Ok(KeyData::new(self.key_buffer))
+    }
+
+    /// Read the display buffer from the HT16K33 chip.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = HT16K33::new(i2c, address);
+    /// ht16k33.read_display_buffer();
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_display_buffer(&mut self) -> Result<(), E> {
+        let mut buffer = self.read_display_ram()?;
+        self.dirty = [false; ROWS_SIZE];
+
+        // The canary is not part of the display buffer, and its ROW still needs to be written if the chip
+        // does not hold it.
+        if let Some(canary) = self.canary {
+            let row = canary.row_as_index();
+
+            self.dirty[row] = !buffer[row].contains(canary.common);
+            buffer[row].remove(canary.common);
+        }
+
+        *self.state.display_buffer_mut() = buffer;
+
+        Ok(())
+    }
+
+    // Read the display RAM, converted from the board wiring to the display buffer order.
+    fn read_display_ram(&mut self) -> Result<[DisplayData; ROWS_SIZE], E> {
+        let mut read_buffer = [0u8; DISPLAY_RAM_BYTES];
+
+        self.i2c.write_read(
+            self.address,
+            &[DisplayDataAddress::ROW_0.bits()],
+            &mut read_buffer,
+        )?;
+
+        Ok(self.state.map_from_display_ram(&read_buffer))
+    }
+}
+
+/// Methods that borrow an I2C device per call instead of using the owned one.
+///
+/// These allow several HT16K33 chips and other peripherals to share one bus, by creating each driver without an
+/// I2C device, e.g. `HT16K33::new((), address)`, and passing the bus to every call. The driver state is updated
+/// exactly as by the corresponding owned methods.
+impl<I2C> HT16K33<I2C> {
+    /// Control the oscillator, using the given I2C device.
+    ///
+    /// See [set_oscillator()](struct.HT16K33.html#method.set_oscillator).
+    ///
+    /// # Arguments
+    ///
+    /// * `i2c` - The I2C device to communicate with the HT16K33 chip.
+    /// * `oscillator` - Set the oscillator On/Off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::Oscillator;
+    /// # fn main() -> Result<(), Error> {
+    /// # let address = 0u8;
+    ///
+    /// // The I2C device is owned by the application, e.g. shared with other devices on the bus.
+    /// let mut i2c = I2cMock::new();
+    /// let mut ht16k33 = HT16K33::new((), address);
+    ///
+    /// ht16k33.set_oscillator_with(&mut i2c, Oscillator::ON)?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_oscillator_with<B, BE>(
+        &mut self,
+        i2c: &mut B,
+        oscillator: Oscillator,
+    ) -> Result<(), BE>
+    where
+        B: Write<Error = BE>,
+    {
+        self.state.set_oscillator(oscillator);
+
+        Self::write_register_to(
+            i2c,
+            self.address,
+            self.state.system_setup_bytes(),
+            &mut self.oscillator_confirmed,
+            oscillator,
+        )
+    }
+
+    /// Control the display, using the given I2C device.
+    ///
+    /// See [set_display()](struct.HT16K33.html#method.set_display).
+    ///
+    /// # Arguments
+    ///
+    /// * `i2c` - The I2C device to communicate with the HT16K33 chip.
+    /// * `display` - Set the display On/Off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::Display;
+    /// # fn main() -> Result<(), Error> {
+    /// # let address = 0u8;
+    ///
+    /// // The I2C device is owned by the application, e.g. shared with other devices on the bus.
+    /// let mut i2c = I2cMock::new();
+    /// let mut ht16k33 = HT16K33::new((), address);
+    ///
+    /// ht16k33.set_display_with(&mut i2c, Display::HALF_HZ)?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_display_with<B, BE>(&mut self, i2c: &mut B, display: Display) -> Result<(), BE>
+    where
+        B: Write<Error = BE>,
+    {
+        if display.contains(Display::ON) {
+            self.wake_with(i2c)?;
+        }
+
+        self.state.set_display(display);
+
+        Self::write_register_to(
+            i2c,
+            self.address,
+            self.state.display_setup_bytes(),
+            &mut self.display_confirmed,
+            display,
+        )
+    }
+
+    /// Control the display dimming, using the given I2C device.
+    ///
+    /// See [set_dimming()](struct.HT16K33.html#method.set_dimming).
+    ///
+    /// # Arguments
+    ///
+    /// * `i2c` - The I2C device to communicate with the HT16K33 chip.
+    /// * `dimming` - Set the dimming brightness.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::Dimming;
+    /// # fn main() -> Result<(), Error> {
+    /// # let address = 0u8;
+    ///
+    /// // The I2C device is owned by the application, e.g. shared with other devices on the bus.
+    /// let mut i2c = I2cMock::new();
+    /// let mut ht16k33 = HT16K33::new((), address);
+    ///
+    /// ht16k33.set_dimming_with(&mut i2c, Dimming::from_u8(4)?)?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_dimming_with<B, BE>(&mut self, i2c: &mut B, dimming: Dimming) -> Result<(), BE>
+    where
+        B: Write<Error = BE>,
+    {
+        if !self.update_dimming(dimming) {
+            return Ok(());
+        }
+
+        Self::write_register_to(
+            i2c,
+            self.address,
+            self.state.dimming_set_bytes(),
+            &mut self.dimming_confirmed,
+            dimming,
+        )
+    }
+
+    /// Control an LED, using the given I2C device.
+    ///
+    /// See [set_led()](struct.HT16K33.html#method.set_led).
+    ///
+    /// # Arguments
+    ///
+    /// * `i2c` - The I2C device to communicate with the HT16K33 chip.
+    /// * `location` - The LED location to update.
+    /// * `enabled` - Set the LED on (true) or off (false).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::LedLocation;
+    /// # fn main() -> Result<(), Error> {
+    /// # let address = 0u8;
+    ///
+    /// // The I2C device is owned by the application, e.g. shared with other devices on the bus.
+    /// let mut i2c = I2cMock::new();
+    /// let mut ht16k33 = HT16K33::new((), address);
+    ///
+    /// ht16k33.set_led_with(&mut i2c, LedLocation::new(0, 0)?, true)?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_led_with<B, BE>(
+        &mut self,
+        i2c: &mut B,
+        location: LedLocation,
+        enabled: bool,
+    ) -> Result<(), BE>
+    where
+        B: Write<Error = BE>,
+    {
+        self.update_display_buffer(location, enabled);
+        self.wake_with(i2c)?;
+
+        // Only this row is written, other pending changes still need to be flushed.
+        let mut rows = [false; ROWS_SIZE];
+        rows[location.row_as_index()] = true;
+
+        Self::write_buffer_rows_to(
+            i2c,
+            self.address,
+            self.max_write_len,
+            &self.state,
+            self.canary,
+            &mut self.dirty,
+            &rows,
+        )
+    }
+
+    /// Write the display buffer to the HT16K33 chip, using the given I2C device.
+    ///
+    /// See [write_display_buffer()](struct.HT16K33.html#method.write_display_buffer).
+    ///
+    /// # Arguments
+    ///
+    /// * `i2c` - The I2C device to communicate with the HT16K33 chip.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::LedLocation;
+    /// # fn main() -> Result<(), Error> {
+    ///
+    /// // One bus shared by two chips.
+    /// let mut i2c = I2cMock::new();
+    ///
+    /// let mut left = HT16K33::new((), 112u8);
+    /// let mut right = HT16K33::new((), 113u8);
+    ///
+    /// left.update_display_buffer(LedLocation::new(0, 0)?, true);
+    /// right.update_display_buffer(LedLocation::new(0, 1)?, true);
+    ///
+    /// left.write_display_buffer_with(&mut i2c)?;
+    /// right.write_display_buffer_with(&mut i2c)?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_display_buffer_with<B, BE>(&mut self, i2c: &mut B) -> Result<(), BE>
+    where
+        B: Write<Error = BE>,
+    {
+        self.wake_with(i2c)?;
+
+        Self::write_buffer_rows_to(
+            i2c,
+            self.address,
+            self.max_write_len,
+            &self.state,
+            self.canary,
+            &mut self.dirty,
+            &[true; ROWS_SIZE],
+        )
+    }
+
+    /// Read the key data RAM from the HT16K33 chip, using the given I2C device.
+    ///
+    /// See [read_key_data()](struct.HT16K33.html#method.read_key_data).
+    ///
+    /// # Arguments
+    ///
+    /// * `i2c` - The I2C device to communicate with the HT16K33 chip.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// # use ht16k33::HT16K33;
+    /// use ht16k33::KeyData;
+    /// # fn main() -> Result<(), Error> {
+    /// # let address = 0u8;
+    ///
+    /// // The I2C device is owned by the application, e.g. shared with other devices on the bus.
+    /// let mut i2c = I2cMock::new();
+    /// let mut ht16k33 = HT16K33::new((), address);
+    ///
+    /// let key_data: KeyData = ht16k33.read_key_data_with(&mut i2c)?;
+    ///
+    /// if key_data.any_pressed() {
+    ///     // Handle the key press.
+    /// }
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_key_data_with<B, BE>(&mut self, i2c: &mut B) -> Result<KeyData, BE>
+    where
+        B: Write<Error = BE> + WriteRead<Error = BE>,
+    {
+        self.wake_with(i2c)?;
+
+        i2c.write_read(self.address, &[KEY_DATA_ADDRESS], &mut self.key_buffer)?;
+
+        Ok(KeyData::new(self.key_buffer))
+    }
+
+    // Turn the oscillator on with the given I2C device if auto-wake is enabled and the chip is in standby, see
+    // `wake()`.
+    fn wake_with<B, BE>(&mut self, i2c: &mut B) -> Result<(), BE>
+    where
+        B: Write<Error = BE>,
+    {
+        if self.auto_wake && self.power_state() == PowerState::Standby {
+            self.set_oscillator_with(i2c, Oscillator::ON)?;
+        }
+
+        Ok(())
+    }
+
+    // Update the desired dimming, or record that it is ignored. Return whether it needs to be written.
+    fn update_dimming(&mut self, dimming: Dimming) -> bool {
+        self.dimming_ignored = !self.dimming_supported;
+        if !self.dimming_ignored {
+            self.state.set_dimming(dimming);
+        }
+
+        !self.dimming_ignored
+    }
+
+    // Write a setup register command, and confirm its value once it has been written.
+    fn write_register_to<B, BE, R>(
+        i2c: &mut B,
+        address: u8,
+        bytes: [u8; 1],
+        confirmed: &mut R,
+        value: R,
+    ) -> Result<(), BE>
+    where
+        B: Write<Error = BE>,
+    {
+        i2c.write(address, &bytes)?;
+
+        *confirmed = value;
+
+        Ok(())
+    }
+
+    // Write the selected display buffer rows, and mark them clean once they have been written.
+    fn write_buffer_rows_to<B, BE>(
+        i2c: &mut B,
+        address: u8,
+        max_write_len: usize,
+        state: &Ht16k33State,
+        canary: Option<LedLocation>,
+        dirty: &mut [bool; ROWS_SIZE],
+        rows: &[bool; ROWS_SIZE],
+    ) -> Result<(), BE>
+    where
+        B: Write<Error = BE>,
+    {
+        // A failed write may have been partially applied by the device, so the
+        // rows are only known to match the device after a successful write.
+        for (dirty, &row) in dirty.iter_mut().zip(rows.iter()) {
+            *dirty |= row;
+        }
+
+        Self::write_rows_to(
+            i2c,
+            address,
+            max_write_len,
+            state,
+            canary,
+            state.display_buffer(),
+            rows,
+        )?;
+
+        for (dirty, &row) in dirty.iter_mut().zip(rows.iter()) {
+            *dirty &= !row;
+        }
+
+        Ok(())
+    }
+
+    // Write the selected buffer rows, split into writes of at most `max_write_len` bytes.
+    //
+    // The rows are converted to the board wiring first, and then each run of contiguous display RAM rows is
+    // written in ascending address order, so the transactions only depend on which rows are written.
+    //
+    // The canary LED is added to every frame, whatever layer or effect it comes from, so that the chip always
+    // holds it for `validate_state()`.
+    //
+    // The I2C device and the driver state are passed separately, so that the driver's own I2C device can be
+    // borrowed alongside its state.
+    fn write_rows_to<B, BE>(
+        i2c: &mut B,
+        address: u8,
+        max_write_len: usize,
+        state: &Ht16k33State,
+        canary: Option<LedLocation>,
+        buffer: &[DisplayData; ROWS_SIZE],
+        rows: &[bool; ROWS_SIZE],
+    ) -> Result<(), BE>
+    where
+        B: Write<Error = BE>,
+    {
+        let rows_per_write = max_write_len - 1;
+
+        let mut frame = *buffer;
+        if let Some(canary) = canary {
+            frame[canary.row_as_index()].insert(canary.common);
+        }
+
+        let ram = state.map_to_display_ram(&frame);
+        let mut pending = [false; ROWS_SIZE];
+
+        for (row, ram_row) in state.row_map().iter().enumerate() {
+            pending[ram_row.bits() as usize] = rows[row];
+        }
+
+        let mut row = 0;
+        while row < ROWS_SIZE {
+            if !pending[row] {
+                row += 1;
+                continue;
+            }
+
+            let mut length = 0;
+            while row + length < ROWS_SIZE && pending[row + length] && length < rows_per_write {
+                length += 1;
+            }
+
+            let mut write_buffer = [0u8; ROWS_SIZE + 1];
+            write_buffer[0] = DisplayDataAddress::from_bits_truncate(row as u8).bits();
+
+            for (index, data) in ram[row..row + length].iter().enumerate() {
+                write_buffer[index + 1] = data.bits();
+            }
+
+            i2c.write(address, &write_buffer[..=length])?;
+
+            row += length;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+// The tests index the buffers by address to mirror the device layout.
+#[allow(clippy::needless_range_loop)]
+mod tests {
+    extern crate std;
+    use embedded_hal_mock as hal;
+
+    use self::hal::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+    use super::*;
+    use crate::state::ROW_MAP_IDENTITY;
+
+    use std::vec;
+
+    const ADDRESS: u8 = 0;
+
+    /// An I2C device where every transaction fails.
+    struct FailingI2c;
+
+    impl Write for FailingI2c {
+        type Error = ();
+
+        fn write(&mut self, _address: u8, _bytes: &[u8]) -> Result<(), ()> {
+            Err(())
+        }
+    }
+
+    impl WriteRead for FailingI2c {
+        type Error = ();
+
+        fn write_read(
+            &mut self,
+            _address: u8,
+            _bytes: &[u8],
+            _buffer: &mut [u8],
+        ) -> Result<(), ()> {
+            Err(())
+        }
+    }
+
+    // Fails every transaction while `fail` is set, otherwise forwards to `i2c`.
+    struct FlakyI2c<I2C> {
+        i2c: I2C,
+        fail: bool,
+    }
+
+    impl<I2C: Write> Write for FlakyI2c<I2C> {
+        type Error = Option<I2C::Error>;
+
+        fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+            if self.fail {
+                return Err(None);
+            }
+
+            self.i2c.write(address, bytes).map_err(Some)
+        }
+    }
+
+    impl<I2C: WriteRead> WriteRead for FlakyI2c<I2C> {
+        type Error = Option<I2C::Error>;
+
+        fn write_read(
+            &mut self,
+            address: u8,
+            bytes: &[u8],
+            buffer: &mut [u8],
+        ) -> Result<(), Self::Error> {
+            if self.fail {
+                return Err(None);
+            }
+
+            self.i2c.write_read(address, bytes, buffer).map_err(Some)
+        }
+    }
+
+    #[test]
+    fn new() {
+        let expectations = [];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn initialize() {
+        let mut write_buffer = vec![super::DisplayDataAddress::ROW_0.bits()];
+        write_buffer.extend([0; super::ROWS_SIZE].iter().cloned());
+
+        let expectations = [
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Oscillator::COMMAND | super::Oscillator::ON).bits()],
+            ),
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Display::COMMAND | super::Display::OFF).bits()],
+            ),
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Dimming::COMMAND | Dimming::BRIGHTNESS_MAX).bits()],
+            ),
+            I2cTransaction::write(ADDRESS, write_buffer),
+        ];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        ht16k33.initialize().unwrap();
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn initialize_emulated() {
+        let mut i2c = crate::i2c_mock::I2cMock::new();
+        i2c.data_values = [0xFF; ROWS_SIZE];
+
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        ht16k33.initialize().unwrap();
+
+        let i2c = ht16k33.destroy();
+        assert_eq!(i2c.oscillator, Oscillator::ON);
+        assert_eq!(i2c.display, Display::OFF);
+        assert_eq!(i2c.dimming, Dimming::BRIGHTNESS_MAX);
+        assert_eq!(i2c.data_values, [0; ROWS_SIZE]);
+    }
+
+    #[test]
+    fn initialize_with() {
+        let mut write_buffer = vec![super::DisplayDataAddress::ROW_0.bits()];
+        write_buffer.extend([0; super::ROWS_SIZE].iter().cloned());
+
+        let expectations = [
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Oscillator::COMMAND | super::Oscillator::ON).bits()],
+            ),
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Dimming::COMMAND | Dimming::BRIGHTNESS_4_16).bits()],
+            ),
+            I2cTransaction::write(ADDRESS, write_buffer),
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Display::COMMAND | super::Display::ON).bits()],
+            ),
+        ];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        let options = InitOptions {
+            brightness: Dimming::BRIGHTNESS_4_16,
+            display: Display::ON,
+            ramp: None,
+        };
+        ht16k33
+            .initialize_with(options, &mut embedded_hal_mock::delay::MockNoop::new())
+            .unwrap();
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn initialize_with_ramp() {
+        let mut write_buffer = vec![super::DisplayDataAddress::ROW_0.bits()];
+        write_buffer.extend([0; super::ROWS_SIZE].iter().cloned());
+
+        let expectations = [
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Oscillator::COMMAND | super::Oscillator::ON).bits()],
+            ),
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Dimming::COMMAND | Dimming::BRIGHTNESS_MIN).bits()],
+            ),
+            I2cTransaction::write(ADDRESS, write_buffer),
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Display::COMMAND | super::Display::ON).bits()],
+            ),
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Dimming::COMMAND | Dimming::BRIGHTNESS_2_16).bits()],
+            ),
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Dimming::COMMAND | Dimming::BRIGHTNESS_3_16).bits()],
+            ),
+        ];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        let options = InitOptions {
+            brightness: Dimming::BRIGHTNESS_3_16,
+            display: Display::ON,
+            ramp: Some(RampMs(100)),
+        };
+        ht16k33
+            .initialize_with(options, &mut embedded_hal_mock::delay::MockNoop::new())
+            .unwrap();
+
+        assert_eq!(*ht16k33.dimming(), Dimming::BRIGHTNESS_3_16);
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn display_buffer() {
+        let expectations = [];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        let &buffer = ht16k33.display_buffer();
+
+        // Ensure it's the expected size.
+        assert_eq!(buffer.len(), ROWS_SIZE);
+
+        for row in buffer.iter() {
+            // And because we just initialized this buffer, it should be all zeros.
+            assert_eq!(row.bits(), 0u8);
+        }
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn view() {
+        let expectations = [];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        let location = LedLocation::new(3, 4).unwrap();
+        ht16k33.update_display_buffer(location, true);
+
+        let view = ht16k33.view();
+
+        assert!(view.is_led_on(location));
+        assert_eq!(view.display_buffer(), ht16k33.display_buffer());
+        assert_eq!(view.oscillator(), *ht16k33.oscillator());
+        assert_eq!(view.display(), *ht16k33.display());
+        assert_eq!(view.dimming(), *ht16k33.dimming());
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn key_buffer() {
+        let expectations = [];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        let &key_buffer = ht16k33.key_buffer();
+
+        assert_eq!(key_buffer, [0u8; KEY_DATA_SIZE]);
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn needs_flush() {
+        let mut write_buffer = vec![super::DisplayDataAddress::ROW_0.bits()];
+        write_buffer.extend([0; super::ROWS_SIZE].iter().cloned());
+
+        let expectations = [
+            I2cTransaction::write(ADDRESS, write_buffer),
+            I2cTransaction::write(ADDRESS, vec![1u8, 0b1000_0000]),
+        ];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        // The device RAM is undefined until the buffer is written.
+        assert!(ht16k33.needs_flush());
+
+        ht16k33.write_display_buffer().unwrap();
+        assert!(!ht16k33.needs_flush());
+
+        // Writing a single LED to a synchronized buffer keeps it synchronized.
+        ht16k33
+            .set_led(LedLocation::new(1, 7).unwrap(), true)
+            .unwrap();
+        assert!(!ht16k33.needs_flush());
+
+        ht16k33.update_display_buffer(LedLocation::new(2, 2).unwrap(), true);
+        assert!(ht16k33.needs_flush());
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn needs_flush_after_failed_write() {
+        let mut ht16k33 = HT16K33::new(FailingI2c, ADDRESS);
+
+        // Pretend the buffer was synchronized, the failed write may have been partially applied.
+        ht16k33.dirty = [false; ROWS_SIZE];
+
+        assert!(ht16k33.write_display_buffer().is_err());
+        assert!(ht16k33.needs_flush());
+    }
+
+    #[test]
+    fn oscillator() {
+        let expectations = [];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        let &oscillator = ht16k33.oscillator();
+
+        assert_eq!(oscillator, Oscillator::OFF);
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn display() {
+        let expectations = [];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        let &display = ht16k33.display();
+
+        assert_eq!(display, Display::OFF);
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn dimming() {
+        let expectations = [];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        let &dimming = ht16k33.dimming();
+
+        assert_eq!(dimming, Dimming::BRIGHTNESS_MAX);
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn capabilities() {
+        let expectations = [];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        let capabilities = ht16k33.capabilities();
+
+        assert_eq!(capabilities, Capabilities::SOP28);
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn update_display_buffer() {
+        let expectations = [];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        let first_led = LedLocation::new(1, 4).unwrap();
+        let second_led = LedLocation::new(1, 5).unwrap();
+
+        // Turn on the LED.
+        ht16k33.update_display_buffer(first_led, true);
+        assert_eq!(ht16k33.display_buffer()[1].bits(), 0b0001_0000);
+
+        // Turn on another LED.
+        ht16k33.update_display_buffer(second_led, true);
+        assert_eq!(ht16k33.display_buffer()[1].bits(), 0b0011_0000);
+
+        // Turn off the first LED.
+        ht16k33.update_display_buffer(first_led, false);
+        assert_eq!(ht16k33.display_buffer()[1].bits(), 0b0010_0000);
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn clear_display_buffer() {
+        let expectations = [];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        let first_led = LedLocation::new(1, 4).unwrap();
+        let second_led = LedLocation::new(1, 5).unwrap();
+
+        // Turn on the LEDs.
+        ht16k33.update_display_buffer(first_led, true);
+        ht16k33.update_display_buffer(second_led, true);
+
+        // Clear the display buffer.
+        ht16k33.clear_display_buffer();
+
+        let &buffer = ht16k33.display_buffer();
+
+        // Ensure it's still the expected size.
+        assert_eq!(buffer.len(), ROWS_SIZE);
+
+        for row in buffer.iter() {
+            // We just cleared this buffer, it should be all zeros.
+            assert_eq!(row.bits(), 0u8);
+        }
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn fill_region() {
+        let mut ht16k33 = HT16K33::new(crate::i2c_mock::I2cMock::new(), ADDRESS);
+        ht16k33.write_display_buffer().unwrap();
+
+        ht16k33.fill_region(Region::new(2, 4, 6, 8).unwrap(), true);
+
+        for (row, data) in ht16k33.display_buffer().iter().enumerate() {
+            let expected = if row == 2 || row == 3 {
+                DisplayData::COMMON_6 | DisplayData::COMMON_7
+            } else {
+                DisplayData::COMMON_NONE
+            };
+
+            assert_eq!(expected, *data, "row [{}]", row);
+            assert_eq!(row == 2 || row == 3, ht16k33.dirty_rows()[row]);
+        }
+
+        // Only the region is turned off.
+        ht16k33.fill_region(Region::all(), true);
+        ht16k33.fill_region(Region::new(0, 16, 0, 1).unwrap(), false);
+
+        for data in ht16k33.display_buffer().iter() {
+            assert_eq!(DisplayData::all() - DisplayData::COMMON_0, *data);
+        }
+    }
+
+    #[test]
+    fn set_canary() {
+        let mut ht16k33 = HT16K33::new(crate::i2c_mock::I2cMock::new(), ADDRESS);
+        ht16k33.write_display_buffer().unwrap();
+
+        let first_canary = LedLocation::new(15, 7).unwrap();
+        let second_canary = LedLocation::new(14, 7).unwrap();
+
+        // The canary is written to the chip, but not stored in the display buffer.
+        ht16k33.set_canary(Some(first_canary));
+        assert_eq!(ht16k33.canary(), Some(first_canary));
+        assert!(ht16k33.dirty_rows()[15]);
+
+        ht16k33.write_dirty().unwrap();
+        assert_eq!(ht16k33.display_buffer()[15], DisplayData::COMMON_NONE);
+        assert_eq!(ht16k33.i2c.data_values[15], DisplayData::COMMON_7.bits());
+
+        // Moving the canary releases the previous location.
+        ht16k33.set_canary(Some(second_canary));
+        ht16k33.write_dirty().unwrap();
+        assert_eq!(ht16k33.i2c.data_values[15], 0);
+        assert_eq!(ht16k33.i2c.data_values[14], DisplayData::COMMON_7.bits());
+
+        // The canary survives clearing the buffer.
+        ht16k33.clear_display_buffer();
+        ht16k33.write_display_buffer().unwrap();
+        assert_eq!(ht16k33.i2c.data_values[14], DisplayData::COMMON_7.bits());
+
+        ht16k33.set_canary(None);
+        assert_eq!(ht16k33.canary(), None);
+        ht16k33.write_dirty().unwrap();
+        assert_eq!(ht16k33.i2c.data_values[14], 0);
+    }
+
+    #[test]
+    fn canary_survives_drawing() {
+        let mut ht16k33 = HT16K33::new(crate::i2c_mock::I2cMock::new(), ADDRESS);
+        let canary = LedLocation::new(15, 7).unwrap();
+        let neighbor = LedLocation::new(15, 0).unwrap();
+
+        ht16k33.set_canary(Some(canary));
+        ht16k33.write_display_buffer().unwrap();
+
+        // Drawing into the canary ROW.
+        ht16k33.set_led(neighbor, true).unwrap();
+        assert!(ht16k33.validate_state().unwrap());
+
+        // Clearing the canary location itself.
+        ht16k33.set_led(canary, false).unwrap();
+        assert!(ht16k33.validate_state().unwrap());
+
+        ht16k33.update_display_buffer(canary, false);
+        ht16k33.write_display_buffer().unwrap();
+        assert!(ht16k33.validate_state().unwrap());
+
+        ht16k33
+            .write_commons(&[(DisplayDataAddress::ROW_15, DisplayData::COMMON_NONE)])
+            .unwrap();
+        assert!(ht16k33.validate_state().unwrap());
+    }
+
+    #[test]
+    fn canary_survives_effect() {
+        let mut ht16k33 = HT16K33::new(crate::i2c_mock::I2cMock::new(), ADDRESS);
+        let canary = LedLocation::new(15, 7).unwrap();
+
+        ht16k33.set_canary(Some(canary));
+
+        // The canary is added after the effect, so inverting does not clear it.
+        ht16k33
+            .write_display_buffer_effect(&mut super::Invert, 0)
+            .unwrap();
+        assert!(ht16k33.validate_state().unwrap());
+        assert_eq!(ht16k33.i2c.data_values[15], 0xFF);
+
+        ht16k33
+            .write_display_buffer_effect(
+                &mut |frame: &mut [DisplayData; ROWS_SIZE], _| frame[15] = DisplayData::empty(),
+                0,
+            )
+            .unwrap();
+        assert!(ht16k33.validate_state().unwrap());
+    }
+
+    #[test]
+    fn canary_read_display_buffer() {
+        let mut ht16k33 = HT16K33::new(crate::i2c_mock::I2cMock::new(), ADDRESS);
+        let canary = LedLocation::new(15, 7).unwrap();
+
+        ht16k33.set_canary(Some(canary));
+        ht16k33.update_display_buffer(LedLocation::new(15, 0).unwrap(), true);
+        ht16k33.write_display_buffer().unwrap();
+
+        // The canary is not read back into the display buffer.
+        ht16k33.read_display_buffer().unwrap();
+        assert_eq!(ht16k33.display_buffer()[15], DisplayData::COMMON_0);
+        assert!(!ht16k33.needs_flush());
+
+        // And the next write still includes it.
+        ht16k33.write_display_buffer().unwrap();
+        assert!(ht16k33.validate_state().unwrap());
+
+        // If the chip lost the canary, its ROW still needs to be written.
+        ht16k33.i2c.data_values[15] = 0;
+        ht16k33.read_display_buffer().unwrap();
+        assert!(!ht16k33.validate_state().unwrap());
+        assert!(ht16k33.dirty_rows()[15]);
+
+        ht16k33.write_dirty().unwrap();
+        assert!(ht16k33.validate_state().unwrap());
+    }
+
+    #[test]
+    fn validate_state() {
+        let expectations = [
+            I2cTransaction::write_read(ADDRESS, vec![15u8], vec![0b1000_0000]),
+            I2cTransaction::write_read(ADDRESS, vec![15u8], vec![0b0000_0000]),
+        ];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        // Nothing to validate without a canary.
+        assert!(ht16k33.validate_state().unwrap());
+
+        ht16k33.set_canary(Some(LedLocation::new(15, 7).unwrap()));
+
+        assert!(ht16k33.validate_state().unwrap());
+        assert!(!ht16k33.validate_state().unwrap());
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn set_oscillator() {
+        let expectations = [I2cTransaction::write(
+            ADDRESS,
+            vec![(super::Oscillator::COMMAND | super::Oscillator::OFF).bits()],
+        )];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        ht16k33.set_oscillator(super::Oscillator::OFF).unwrap();
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn set_display() {
+        let expectations = [I2cTransaction::write(
+            ADDRESS,
+            vec![(super::Display::COMMAND | super::Display::OFF).bits()],
+        )];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        ht16k33.set_display(super::Display::OFF).unwrap();
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn set_dimming() {
+        let expectations = [I2cTransaction::write(
+            ADDRESS,
+            vec![(super::Dimming::COMMAND | Dimming::BRIGHTNESS_MAX).bits()],
+        )];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        ht16k33.set_dimming(Dimming::BRIGHTNESS_MAX).unwrap();
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn is_synced() {
+        let mut ht16k33 = HT16K33::new(FailingI2c, ADDRESS);
+
+        assert!(ht16k33.is_synced());
+
+        assert!(ht16k33.set_display(Display::ON).is_err());
+        assert!(!ht16k33.is_synced());
+        assert_eq!(*ht16k33.display(), Display::ON);
+        assert_eq!(*ht16k33.confirmed_display(), Display::OFF);
+
+        assert!(ht16k33.set_display(Display::OFF).is_err());
+        assert!(ht16k33.is_synced());
+
+        assert!(ht16k33.set_dimming(Dimming::BRIGHTNESS_MIN).is_err());
+        assert!(!ht16k33.is_synced());
+        assert_eq!(*ht16k33.confirmed_dimming(), Dimming::BRIGHTNESS_MAX);
+
+        // Unsupported dimming is ignored.
+        ht16k33.set_dimming_supported(false);
+        assert!(ht16k33.is_synced());
+    }
+
+    #[test]
+    fn sync() {
+        let expectations = [
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Oscillator::COMMAND | Oscillator::ON).bits()],
+            ),
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Display::COMMAND | Display::ONE_HZ).bits()],
+            ),
+        ];
+
+        let i2c = FlakyI2c {
+            i2c: I2cMock::new(&expectations),
+            fail: true,
+        };
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        assert!(ht16k33.set_oscillator(Oscillator::ON).is_err());
+        assert!(ht16k33.set_display(Display::ONE_HZ).is_err());
+
+        // Only the failed writes are retried.
+        ht16k33.i2c.fail = false;
+
+        ht16k33.sync().unwrap();
+        assert!(ht16k33.is_synced());
+
+        // Nothing left to write.
+        ht16k33.sync().unwrap();
+
+        let mut i2c = ht16k33.destroy().i2c;
+        i2c.done();
+    }
+
+    #[test]
+    fn set_bit_order() {
+        let mut ht16k33 = HT16K33::new(crate::i2c_mock::I2cMock::new(), ADDRESS);
+        let location = LedLocation::new(1, 0).unwrap();
+
+        ht16k33.set_bit_order(BitOrder::Reversed);
+        assert_eq!(BitOrder::Reversed, ht16k33.bit_order());
+
+        ht16k33.set_led(location, true).unwrap();
+        ht16k33.set_canary(Some(LedLocation::new(2, 0).unwrap()));
+        ht16k33.write_dirty().unwrap();
+        assert!(ht16k33.validate_state().unwrap());
+
+        // Reading back converts to the logical order.
+        ht16k33.read_display_buffer().unwrap();
+        assert!(ht16k33.view().is_led_on(location));
+
+        let i2c = ht16k33.destroy();
+        assert_eq!(i2c.data_values[1], DisplayData::COMMON_7.bits());
+    }
+
+    #[test]
+    fn set_row_map() {
+        let mut row_map = ROW_MAP_IDENTITY;
+        row_map.swap(0, 15);
+        row_map.swap(1, 8);
+
+        let expectations = [
+            // Only the written rows, as runs of contiguous display RAM rows.
+            I2cTransaction::write(ADDRESS, vec![8u8, 0b0000_0010]),
+            I2cTransaction::write(ADDRESS, vec![2u8, 0b0000_0100, 0]),
+            I2cTransaction::write(ADDRESS, vec![15u8, 0b0000_0001]),
+        ];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        ht16k33.set_row_map(row_map).unwrap();
+        assert_eq!(DisplayDataAddress::ROW_15, ht16k33.row_map()[0]);
+
+        ht16k33
+            .set_led(LedLocation::new(1, 1).unwrap(), true)
+            .unwrap();
+        ht16k33
+            .write_commons(&[
+                (DisplayDataAddress::ROW_0, DisplayData::COMMON_0),
+                (DisplayDataAddress::ROW_2, DisplayData::COMMON_2),
+                (DisplayDataAddress::ROW_3, DisplayData::empty()),
+            ])
+            .unwrap();
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    // Records the bytes of every write.
+    #[derive(Default)]
+    struct RecordingI2c {
+        writes: std::vec::Vec<std::vec::Vec<u8>>,
+    }
+
+    impl Write for RecordingI2c {
+        type Error = ();
+
+        fn write(&mut self, _address: u8, bytes: &[u8]) -> Result<(), ()> {
+            self.writes.push(bytes.to_vec());
+            Ok(())
+        }
+    }
+
+    impl WriteRead for RecordingI2c {
+        type Error = ();
+
+        fn write_read(
+            &mut self,
+            _address: u8,
+            _bytes: &[u8],
+            _buffer: &mut [u8],
+        ) -> Result<(), ()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn write_dirty() {
+        let mut ht16k33 = HT16K33::new(RecordingI2c::default(), ADDRESS);
+
+        // Everything is dirty at power-on.
+        ht16k33.write_dirty().unwrap();
+        assert!(!ht16k33.needs_flush());
+
+        ht16k33.update_display_buffer(LedLocation::new(5, 0).unwrap(), true);
+        ht16k33.update_display_buffer(LedLocation::new(4, 1).unwrap(), true);
+        ht16k33.update_display_buffer(LedLocation::new(9, 2).unwrap(), true);
+        assert!(ht16k33.dirty_rows()[4] && ht16k33.dirty_rows()[5] && ht16k33.dirty_rows()[9]);
+
+        ht16k33.write_dirty().unwrap();
+        assert!(!ht16k33.needs_flush());
+
+        // Nothing is written when no rows are dirty.
+        ht16k33.write_dirty().unwrap();
+
+        let mut expected = vec![vec![0u8; ROWS_SIZE + 1]];
+        expected.push(vec![4, 0b0000_0010, 0b0000_0001]);
+        expected.push(vec![9, 0b0000_0100]);
+
+        assert_eq!(expected, ht16k33.destroy().writes);
+    }
+
+    #[test]
+    fn write_dirty_after_set_led() {
+        let mut ht16k33 = HT16K33::new(RecordingI2c::default(), ADDRESS);
+        ht16k33.write_display_buffer().unwrap();
+
+        ht16k33.update_display_buffer(LedLocation::new(1, 0).unwrap(), true);
+        ht16k33
+            .set_led(LedLocation::new(2, 0).unwrap(), true)
+            .unwrap();
+
+        // Only the row written by set_led() is clean.
+        assert!(ht16k33.dirty_rows()[1]);
+        assert!(!ht16k33.dirty_rows()[2]);
+    }
+
+    #[test]
+    fn dirty_after_failed_write() {
+        let i2c = FlakyI2c {
+            i2c: crate::i2c_mock::I2cMock::new(),
+            fail: false,
+        };
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+        ht16k33.write_display_buffer().unwrap();
+
+        ht16k33.i2c.fail = true;
+        assert!(ht16k33
+            .write_commons(&[(DisplayDataAddress::ROW_7, DisplayData::COMMON_1)])
+            .is_err());
+        assert!(ht16k33.dirty_rows()[7]);
+
+        ht16k33.i2c.fail = false;
+        ht16k33.write_dirty().unwrap();
+        assert!(!ht16k33.needs_flush());
+    }
+
+    #[test]
+    fn flush_order_is_deterministic() {
+        let commons = [
+            (DisplayDataAddress::ROW_9, DisplayData::COMMON_1),
+            (DisplayDataAddress::ROW_2, DisplayData::COMMON_2),
+            (DisplayDataAddress::ROW_14, DisplayData::COMMON_3),
+            (DisplayDataAddress::ROW_3, DisplayData::COMMON_4),
+            (DisplayDataAddress::ROW_0, DisplayData::COMMON_5),
+        ];
+
+        let mut row_map = ROW_MAP_IDENTITY;
+        row_map.swap(2, 10);
+
+        let flush = |order: &[usize], row_map| {
+            let mut ht16k33 = HT16K33::new(RecordingI2c::default(), ADDRESS);
+            ht16k33.set_row_map(row_map).unwrap();
+            ht16k33.set_max_write_len(3);
+
+            // Update the buffer in the given order.
+            for &index in order {
+                let (row, common) = commons[index];
+                ht16k33.update_display_buffer(
+                    LedLocation::new(row.bits(), common.common_index().unwrap()).unwrap(),
+                    true,
+                );
+            }
+            ht16k33.write_display_buffer().unwrap();
+
+            // And write the same rows in the given order.
+            let reordered: std::vec::Vec<_> = order.iter().map(|&index| commons[index]).collect();
+            ht16k33.write_commons(&reordered).unwrap();
+
+            ht16k33.destroy().writes
+        };
+
+        for &row_map in [ROW_MAP_IDENTITY, row_map].iter() {
+            let expected = flush(&[0, 1, 2, 3, 4], row_map);
+
+            for order in [[4, 3, 2, 1, 0], [2, 0, 4, 1, 3], [1, 3, 0, 4, 2]].iter() {
+                assert_eq!(expected, flush(order, row_map));
+            }
+
+            // Both flushes start at ROW_0, and then every write starts at a higher address.
+            for writes in expected.split(|write| write[0] == 0) {
+                for pair in writes.windows(2) {
+                    assert!(pair[0][0] < pair[1][0]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn set_row_map_round_trip() {
+        let mut row_map = ROW_MAP_IDENTITY;
+        row_map.reverse();
+
+        let mut ht16k33 = HT16K33::new(crate::i2c_mock::I2cMock::new(), ADDRESS);
+        ht16k33.set_row_map(row_map).unwrap();
+        ht16k33.set_bit_order(BitOrder::Reversed);
+
+        for row in 0..ROWS_SIZE as u8 {
+            ht16k33.update_display_buffer(LedLocation::new(row, row % 8).unwrap(), true);
+        }
+
+        let buffer = *ht16k33.display_buffer();
+
+        ht16k33.write_display_buffer().unwrap();
+        ht16k33.clear_display_buffer();
+        ht16k33.read_display_buffer().unwrap();
+
+        assert_eq!(&buffer, ht16k33.display_buffer());
+
+        let i2c = ht16k33.destroy();
+        assert_eq!(i2c.data_values[15], DisplayData::COMMON_7.bits());
+        assert_eq!(i2c.data_values[0], DisplayData::COMMON_0.bits());
+    }
+
+    #[test]
+    fn set_row_map_duplicate() {
+        let mut ht16k33 = HT16K33::new(crate::i2c_mock::I2cMock::new(), ADDRESS);
+
+        let mut row_map = ROW_MAP_IDENTITY;
+        row_map[0] = DisplayDataAddress::ROW_15;
+
+        assert!(ht16k33.set_row_map(row_map).is_err());
+        assert_eq!(&ROW_MAP_IDENTITY, ht16k33.row_map());
+    }
+
+    #[test]
+    fn auto_wake() {
+        let location = LedLocation::new(1, 0).unwrap();
+        let oscillator_on = I2cTransaction::write(
+            ADDRESS,
+            vec![(super::Oscillator::COMMAND | Oscillator::ON).bits()],
+        );
+
+        let expectations = [
+            // Disabled, so the LED is written in standby.
+            I2cTransaction::write(ADDRESS, vec![1, 1]),
+            // Enabled, so the chip is woken first.
+            oscillator_on.clone(),
+            I2cTransaction::write(ADDRESS, vec![1, 0]),
+            // Already awake.
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Display::COMMAND | Display::ON).bits()],
+            ),
+            // Put back in standby, then woken to read the keys.
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Oscillator::COMMAND | Oscillator::OFF).bits()],
+            ),
+            oscillator_on,
+            I2cTransaction::write_read(
+                ADDRESS,
+                vec![super::KEY_DATA_ADDRESS],
+                vec![0; super::KEY_DATA_SIZE],
+            ),
+        ];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        ht16k33.set_led(location, true).unwrap();
+        assert_eq!(PowerState::Standby, ht16k33.power_state());
+
+        ht16k33.set_auto_wake(true);
+        assert!(ht16k33.auto_wake());
+
+        ht16k33.set_led(location, false).unwrap();
+        assert_eq!(PowerState::Normal, ht16k33.power_state());
+
+        ht16k33.set_display(Display::ON).unwrap();
+
+        ht16k33.set_oscillator(Oscillator::OFF).unwrap();
+        ht16k33.read_key_buffer().unwrap();
+        assert_eq!(PowerState::Normal, ht16k33.power_state());
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn set_dimming_unsupported() {
+        let mut ht16k33 = HT16K33::new(FailingI2c, ADDRESS);
+
+        assert!(ht16k33.set_dimming(Dimming::BRIGHTNESS_MIN).is_err());
+
+        assert!(!ht16k33.dimming_ignored());
+
+        ht16k33.set_dimming_supported(false);
+        assert!(!ht16k33.dimming_supported());
+
+        // No I2C write, so no error.
+        ht16k33.set_dimming(Dimming::BRIGHTNESS_3_16).unwrap();
+        assert_eq!(*ht16k33.dimming(), Dimming::BRIGHTNESS_MIN);
+        assert!(ht16k33.dimming_ignored());
+
+        ht16k33
+            .set_dimming_with(&mut FailingI2c, Dimming::BRIGHTNESS_MAX)
+            .unwrap();
+        assert!(ht16k33.dimming_ignored());
+
+        ht16k33.set_dimming_supported(true);
+        assert!(ht16k33.set_dimming(Dimming::BRIGHTNESS_MAX).is_err());
+        assert!(!ht16k33.dimming_ignored());
+    }
+
+    #[test]
+    fn initialize_dimming_unsupported() {
+        let expectations = [
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Oscillator::COMMAND | Oscillator::ON).bits()],
+            ),
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Display::COMMAND | Display::OFF).bits()],
+            ),
+            // No dimming command.
+            I2cTransaction::write(ADDRESS, {
+                let mut write_buffer = vec![super::DisplayDataAddress::ROW_0.bits()];
+                write_buffer.extend([0; super::ROWS_SIZE].iter().cloned());
+                write_buffer
+            }),
+        ];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        ht16k33.set_dimming_supported(false);
+        ht16k33.initialize().unwrap();
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    #[cfg(feature = "testmode")]
+    fn enter_test_mode() {
+        let expectations = [I2cTransaction::write(ADDRESS, vec![0xD9])];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        ht16k33.enter_test_mode().unwrap();
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn set_led() {
+        let expectations = [I2cTransaction::write(ADDRESS, vec![1u8, 0b1000_0000])];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        ht16k33
+            .set_led(LedLocation::new(1, 7).unwrap(), true)
+            .unwrap();
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn set_leds() {
+        let expectations = [I2cTransaction::write(
+            ADDRESS,
+            vec![2u8, 0b0000_0001, 0b0001_0000, 0b1000_0010],
+        )];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        // A pending change in between is written too.
+        ht16k33.update_display_buffer(LedLocation::new(3, 4).unwrap(), true);
+        ht16k33.update_display_buffer(LedLocation::new(9, 4).unwrap(), true);
+
+        ht16k33
+            .set_leds([
+                (LedLocation::new(4, 1).unwrap(), true),
+                (LedLocation::new(2, 0).unwrap(), true),
+                (LedLocation::new(4, 7).unwrap(), true),
+                (LedLocation::new(4, 3).unwrap(), true),
+                (LedLocation::new(4, 3).unwrap(), false),
+            ])
+            .unwrap();
+
+        assert!(!ht16k33.dirty_rows()[2] && !ht16k33.dirty_rows()[3] && !ht16k33.dirty_rows()[4]);
+        assert!(ht16k33.dirty_rows()[9]);
+
+        // Nothing is written without any LEDs.
+        ht16k33.set_leds(None).unwrap();
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn write_commons() {
+        let expectations = [
+            I2cTransaction::write(ADDRESS, vec![1u8, 0b0000_1000, 0b0000_0100, 0b0000_0001]),
+            I2cTransaction::write(ADDRESS, vec![7u8, 0b1111_1111]),
+            I2cTransaction::write(ADDRESS, vec![15u8, 0b1000_0000]),
+        ];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        ht16k33
+            .write_commons(&[
+                (DisplayDataAddress::ROW_15, DisplayData::COMMON_7),
+                (DisplayDataAddress::ROW_3, DisplayData::COMMON_0),
+                (DisplayDataAddress::ROW_7, DisplayData::all()),
+                (DisplayDataAddress::ROW_1, DisplayData::COMMON_3),
+                (DisplayDataAddress::ROW_2, DisplayData::COMMON_0),
+                // Replaces the previous value for this row.
+                (DisplayDataAddress::ROW_2, DisplayData::COMMON_2),
+            ])
+            .unwrap();
+
+        assert_eq!(ht16k33.display_buffer()[2], DisplayData::COMMON_2);
+        assert_eq!(ht16k33.display_buffer()[15], DisplayData::COMMON_7);
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn write_commons_empty() {
+        let expectations = [];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        ht16k33.write_commons(&[]).unwrap();
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn flash_alert() {
+        let mut all_on = vec![super::DisplayDataAddress::ROW_0.bits()];
+        all_on.extend([0xFF; super::ROWS_SIZE].iter().cloned());
+
+        let mut all_off = vec![super::DisplayDataAddress::ROW_0.bits()];
+        all_off.extend([0; super::ROWS_SIZE].iter().cloned());
+
+        let mut restored = all_off.clone();
+        restored[2] = 0b1000_0000;
+
+        let expectations = [
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Display::COMMAND | super::Display::ON).bits()],
+            ),
+            I2cTransaction::write(ADDRESS, all_on.clone()),
+            I2cTransaction::write(ADDRESS, all_off.clone()),
+            I2cTransaction::write(ADDRESS, all_on),
+            I2cTransaction::write(ADDRESS, all_off),
+            I2cTransaction::write(ADDRESS, restored),
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Display::COMMAND | super::Display::OFF).bits()],
+            ),
+        ];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        ht16k33.update_display_buffer(LedLocation::new(1, 7).unwrap(), true);
+
+        ht16k33
+            .flash_alert(2, 100, 100, &mut embedded_hal_mock::delay::MockNoop::new())
+            .unwrap();
+
+        assert_eq!(ht16k33.display_buffer()[1], DisplayData::COMMON_7);
+        assert_eq!(*ht16k33.display(), Display::OFF);
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn flash_alert_dimming() {
+        let mut all_on = vec![super::DisplayDataAddress::ROW_0.bits()];
+        all_on.extend([0xFF; super::ROWS_SIZE].iter().cloned());
+
+        let mut all_off = vec![super::DisplayDataAddress::ROW_0.bits()];
+        all_off.extend([0; super::ROWS_SIZE].iter().cloned());
+
+        let expectations = [
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Dimming::COMMAND | Dimming::BRIGHTNESS_MIN).bits()],
+            ),
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Display::COMMAND | super::Display::ON).bits()],
+            ),
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Dimming::COMMAND | Dimming::BRIGHTNESS_MAX).bits()],
+            ),
+            I2cTransaction::write(ADDRESS, all_on),
+            I2cTransaction::write(ADDRESS, all_off.clone()),
+            I2cTransaction::write(ADDRESS, all_off),
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Display::COMMAND | super::Display::OFF).bits()],
+            ),
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Dimming::COMMAND | Dimming::BRIGHTNESS_MIN).bits()],
+            ),
+        ];
+
+        let mut i2c = I2cMock::new(&expectations);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        ht16k33.set_dimming(Dimming::BRIGHTNESS_MIN).unwrap();
+
+        ht16k33
+            .flash_alert(1, 100, 100, &mut embedded_hal_mock::delay::MockNoop::new())
+            .unwrap();
+
+        assert_eq!(*ht16k33.dimming(), Dimming::BRIGHTNESS_MIN);
+        assert!(ht16k33.is_synced());
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
+
+    #[test]
+    fn flash_alert_guard() {
+        struct Delays(std::vec::Vec<u16>);
 
-        for (index, value) in read_buffer.iter().enumerate() {
-            self.buffer[index] = DisplayData::from_bits_truncate(*value);
+        impl DelayMs<u16> for Delays {
+            fn delay_ms(&mut self, ms: u16) {
+                self.0.push(ms);
+            }
         }
 
-        Ok(())
+        let mut ht16k33 = HT16K33::new(crate::i2c_mock::I2cMock::new(), ADDRESS);
+        let mut delays = Delays(vec![]);
+
+        ht16k33.flash_alert(1, 100, 100, &mut delays).unwrap();
+        assert_eq!(vec![100, 100], delays.0);
+
+        ht16k33.set_flash_guard(true);
+        assert!(ht16k33.flash_guard());
+
+        delays.0.clear();
+        ht16k33.flash_alert(1, 100, 100, &mut delays).unwrap();
+        assert_eq!(vec![167, 167], delays.0);
+
+        // Safe rates are unchanged.
+        delays.0.clear();
+        ht16k33.flash_alert(1, 1000, 500, &mut delays).unwrap();
+        assert_eq!(vec![1000, 500], delays.0);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    extern crate std;
-    use embedded_hal_mock as hal;
+    #[test]
+    fn write_display_buffer() {
+        let mut write_buffer = vec![super::DisplayDataAddress::ROW_0.bits()];
+        write_buffer.extend([0; super::ROWS_SIZE].iter().cloned());
 
-    use self::hal::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
-    use super::*;
+        let expectations = [I2cTransaction::write(ADDRESS, write_buffer)];
 
-    use std::vec;
+        let mut i2c = I2cMock::new(&expectations);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
 
-    const ADDRESS: u8 = 0;
+        ht16k33.write_display_buffer().unwrap();
+
+        i2c = ht16k33.destroy();
+        i2c.done();
+    }
 
     #[test]
-    fn new() {
-        let expectations = [];
+    fn write_display_buffer_effect() {
+        let mut write_buffer = vec![super::DisplayDataAddress::ROW_0.bits()];
+        write_buffer.extend([0xFF; super::ROWS_SIZE].iter().cloned());
+        write_buffer[1] = 0b1111_1110;
+
+        let expectations = [I2cTransaction::write(ADDRESS, write_buffer)];
 
         let mut i2c = I2cMock::new(&expectations);
-        let ht16k33 = HT16K33::new(i2c, ADDRESS);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        let location = LedLocation::new(0, 0).unwrap();
+        ht16k33.update_display_buffer(location, true);
+
+        ht16k33
+            .write_display_buffer_effect(&mut super::Invert, 0)
+            .unwrap();
+
+        // The display buffer is unchanged.
+        assert!(ht16k33.view().is_led_on(location));
+        assert!(!ht16k33.needs_flush());
 
         i2c = ht16k33.destroy();
         i2c.done();
     }
 
     #[test]
-    fn initialize() {
+    fn service() {
         let mut write_buffer = vec![super::DisplayDataAddress::ROW_0.bits()];
         write_buffer.extend([0; super::ROWS_SIZE].iter().cloned());
+        write_buffer[2] = 0b1000_0000;
+
+        let key_data = vec![0, 0b0000_0010, 0, 0, 0, 0];
 
         let expectations = [
-            I2cTransaction::write(
-                ADDRESS,
-                vec![(super::Oscillator::COMMAND | super::Oscillator::ON).bits()],
-            ),
-            I2cTransaction::write(
-                ADDRESS,
-                vec![(super::Display::COMMAND | super::Display::OFF).bits()],
-            ),
-            I2cTransaction::write(
-                ADDRESS,
-                vec![(super::Dimming::COMMAND | Dimming::BRIGHTNESS_MAX).bits()],
-            ),
             I2cTransaction::write(ADDRESS, write_buffer),
+            I2cTransaction::write_read(ADDRESS, vec![super::KEY_DATA_ADDRESS], key_data.clone()),
         ];
 
         let mut i2c = I2cMock::new(&expectations);
         let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
 
-        ht16k33.initialize().unwrap();
+        ht16k33.update_display_buffer(LedLocation::new(1, 7).unwrap(), true);
+        ht16k33.service().unwrap();
+
+        assert_eq!(&ht16k33.key_buffer()[..], &key_data[..]);
 
         i2c = ht16k33.destroy();
         i2c.done();
     }
 
     #[test]
-    fn display_buffer() {
-        let expectations = [];
+    fn service_display_write_does_not_corrupt_key_read() {
+        let mut i2c = crate::i2c_mock::I2cMock::new();
+        i2c.key_values = [1, 2, 3, 4, 5, 6];
 
-        let mut i2c = I2cMock::new(&expectations);
-        let ht16k33 = HT16K33::new(i2c, ADDRESS);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
 
-        let &buffer = ht16k33.display_buffer();
+        // Fill the whole display, so every display RAM address is written.
+        for row in 0..ROWS_SIZE as u8 {
+            for common in 0..COMMONS_SIZE as u8 {
+                ht16k33.update_display_buffer(LedLocation::new(row, common).unwrap(), true);
+            }
+        }
 
-        // Ensure it's the expected size.
-        assert_eq!(buffer.len(), ROWS_SIZE);
+        ht16k33.service().unwrap();
 
-        for row in buffer.iter() {
-            // And because we just initialized this buffer, it should be all zeros.
-            assert_eq!(row.bits(), 0u8);
-        }
+        assert_eq!(ht16k33.key_buffer(), &[1, 2, 3, 4, 5, 6]);
 
-        i2c = ht16k33.destroy();
-        i2c.done();
+        let i2c = ht16k33.destroy();
+        assert_eq!(i2c.data_values, [0xFF; ROWS_SIZE]);
+        assert_eq!(i2c.key_values, [1, 2, 3, 4, 5, 6]);
     }
 
     #[test]
-    fn oscillator() {
-        let expectations = [];
+    fn keys_changed_since_last_poll() {
+        let key_data = vec![0b0000_0001, 0, 0, 0, 0, 0b0001_0000];
+
+        let expectations = [
+            // First poll always reads the INT flag.
+            I2cTransaction::write_read(ADDRESS, vec![super::INT_FLAG_ADDRESS], vec![0]),
+            // Third poll reads the INT flag, and then the key data.
+            I2cTransaction::write_read(ADDRESS, vec![super::INT_FLAG_ADDRESS], vec![0xFF]),
+            I2cTransaction::write_read(ADDRESS, vec![super::KEY_DATA_ADDRESS], key_data.clone()),
+        ];
 
         let mut i2c = I2cMock::new(&expectations);
-        let ht16k33 = HT16K33::new(i2c, ADDRESS);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
 
-        let &oscillator = ht16k33.oscillator();
+        assert!(!ht16k33.keys_changed_since_last_poll(2).unwrap());
+        assert!(!ht16k33.keys_changed_since_last_poll(2).unwrap());
+        assert!(ht16k33.keys_changed_since_last_poll(2).unwrap());
 
-        assert_eq!(oscillator, Oscillator::OFF);
+        assert_eq!(&ht16k33.key_buffer()[..], &key_data[..]);
 
         i2c = ht16k33.destroy();
         i2c.done();
     }
 
     #[test]
-    fn display() {
-        let expectations = [];
+    fn blank_until_key() {
+        let key_data = vec![0, 0, 0b0000_0100, 0, 0, 0];
+
+        let expectations = [
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Oscillator::COMMAND | Oscillator::ON).bits()],
+            ),
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Display::COMMAND | Display::OFF).bits()],
+            ),
+            I2cTransaction::write_read(ADDRESS, vec![super::INT_FLAG_ADDRESS], vec![0]),
+            I2cTransaction::write_read(ADDRESS, vec![super::INT_FLAG_ADDRESS], vec![0]),
+            I2cTransaction::write_read(ADDRESS, vec![super::INT_FLAG_ADDRESS], vec![0xFF]),
+            I2cTransaction::write_read(ADDRESS, vec![super::KEY_DATA_ADDRESS], key_data.clone()),
+            // Restore the previous states.
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Display::COMMAND | Display::TWO_HZ).bits()],
+            ),
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Oscillator::COMMAND | Oscillator::OFF).bits()],
+            ),
+        ];
 
         let mut i2c = I2cMock::new(&expectations);
-        let ht16k33 = HT16K33::new(i2c, ADDRESS);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
 
-        let &display = ht16k33.display();
+        // Not written, so the state is restored as-is.
+        ht16k33.state.set_display(Display::TWO_HZ);
 
-        assert_eq!(display, Display::OFF);
+        ht16k33
+            .blank_until_key(10, &mut embedded_hal_mock::delay::MockNoop::new())
+            .unwrap();
+
+        assert_eq!(&ht16k33.key_buffer()[..], &key_data[..]);
 
         i2c = ht16k33.destroy();
         i2c.done();
     }
 
     #[test]
-    fn dimming() {
-        let expectations = [];
+    fn set_max_write_len() {
+        let mut first_write = vec![super::DisplayDataAddress::ROW_0.bits()];
+        first_write.extend([0; 6].iter().cloned());
+        first_write[2] = 0b1000_0000;
+
+        let mut second_write = vec![super::DisplayDataAddress::ROW_6.bits()];
+        second_write.extend([0; 6].iter().cloned());
+
+        let mut third_write = vec![super::DisplayDataAddress::ROW_12.bits()];
+        third_write.extend([0; 4].iter().cloned());
+
+        let expectations = [
+            I2cTransaction::write(ADDRESS, first_write),
+            I2cTransaction::write(ADDRESS, second_write),
+            I2cTransaction::write(ADDRESS, third_write),
+            // Merged rows are split too.
+            I2cTransaction::write(ADDRESS, vec![3u8, 0, 0]),
+            I2cTransaction::write(ADDRESS, vec![5u8, 0]),
+        ];
 
         let mut i2c = I2cMock::new(&expectations);
-        let ht16k33 = HT16K33::new(i2c, ADDRESS);
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
 
-        let &dimming = ht16k33.dimming();
+        assert_eq!(ht16k33.max_write_len(), ROWS_SIZE + 1);
 
-        assert_eq!(dimming, Dimming::BRIGHTNESS_MAX);
+        ht16k33.set_max_write_len(7);
+        assert_eq!(ht16k33.max_write_len(), 7);
+
+        ht16k33.update_display_buffer(LedLocation::new(1, 7).unwrap(), true);
+        ht16k33.write_display_buffer().unwrap();
+        assert!(!ht16k33.needs_flush());
+
+        ht16k33.set_max_write_len(3);
+        ht16k33
+            .write_commons(&[
+                (DisplayDataAddress::ROW_3, DisplayData::COMMON_NONE),
+                (DisplayDataAddress::ROW_4, DisplayData::COMMON_NONE),
+                (DisplayDataAddress::ROW_5, DisplayData::COMMON_NONE),
+            ])
+            .unwrap();
 
         i2c = ht16k33.destroy();
         i2c.done();
     }
 
     #[test]
-    fn update_display_buffer() {
+    fn set_max_write_len_minimum() {
         let expectations = [];
 
         let mut i2c = I2cMock::new(&expectations);
         let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
 
-        let first_led = LedLocation::new(1, 4).unwrap();
-        let second_led = LedLocation::new(1, 5).unwrap();
-
-        // Turn on the LED.
-        ht16k33.update_display_buffer(first_led, true);
-        assert_eq!(ht16k33.display_buffer()[1].bits(), 0b0001_0000);
-
-        // Turn on another LED.
-        ht16k33.update_display_buffer(second_led, true);
-        assert_eq!(ht16k33.display_buffer()[1].bits(), 0b0011_0000);
-
-        // Turn off the first LED.
-        ht16k33.update_display_buffer(first_led, false);
-        assert_eq!(ht16k33.display_buffer()[1].bits(), 0b0010_0000);
+        ht16k33.set_max_write_len(0);
+        assert_eq!(ht16k33.max_write_len(), 2);
 
         i2c = ht16k33.destroy();
         i2c.done();
     }
 
     #[test]
-    fn clear_display_buffer() {
-        let expectations = [];
+    fn tick() {
+        let mut base = vec![super::DisplayDataAddress::ROW_0.bits()];
+        base.extend([0; super::ROWS_SIZE].iter().cloned());
+        base[2] = 0b1000_0000;
+
+        let mut overlay = vec![super::DisplayDataAddress::ROW_0.bits()];
+        overlay.extend([0xFF; super::ROWS_SIZE].iter().cloned());
+
+        let expectations = [
+            // Write the base layer only when it has changed.
+            I2cTransaction::write(ADDRESS, base.clone()),
+            // Show the overlay for two ticks.
+            I2cTransaction::write(ADDRESS, overlay.clone()),
+            I2cTransaction::write(ADDRESS, overlay),
+            // And then restore the base layer, including changes made during the overlay.
+            I2cTransaction::write(ADDRESS, {
+                let mut base = base;
+                base[3] = 0b0000_0001;
+                base
+            }),
+        ];
 
         let mut i2c = I2cMock::new(&expectations);
         let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
 
-        let first_led = LedLocation::new(1, 4).unwrap();
-        let second_led = LedLocation::new(1, 5).unwrap();
-
-        // Turn on the LEDs.
-        ht16k33.update_display_buffer(first_led, true);
-        ht16k33.update_display_buffer(second_led, true);
+        ht16k33.update_display_buffer(LedLocation::new(1, 7).unwrap(), true);
+        ht16k33.tick().unwrap();
+        ht16k33.tick().unwrap();
 
-        // Clear the display buffer.
-        ht16k33.clear_display_buffer();
+        ht16k33.set_overlay([DisplayData::all(); ROWS_SIZE], 2);
+        assert!(ht16k33.overlay().is_some());
 
-        let &buffer = ht16k33.display_buffer();
+        ht16k33.tick().unwrap();
+        ht16k33.update_display_buffer(LedLocation::new(2, 0).unwrap(), true);
+        ht16k33.tick().unwrap();
 
-        // Ensure it's still the expected size.
-        assert_eq!(buffer.len(), ROWS_SIZE);
+        ht16k33.tick().unwrap();
+        assert!(ht16k33.overlay().is_none());
+        assert!(!ht16k33.needs_flush());
 
-        for row in buffer.iter() {
-            // We just cleared this buffer, it should be all zeros.
-            assert_eq!(row.bits(), 0u8);
-        }
+        ht16k33.tick().unwrap();
 
         i2c = ht16k33.destroy();
         i2c.done();
     }
 
     #[test]
-    fn set_oscillator() {
-        let expectations = [I2cTransaction::write(
-            ADDRESS,
-            vec![(super::Oscillator::COMMAND | super::Oscillator::OFF).bits()],
-        )];
+    fn clear_overlay() {
+        let mut overlay = vec![super::DisplayDataAddress::ROW_0.bits()];
+        overlay.extend([0xFF; super::ROWS_SIZE].iter().cloned());
+
+        let mut base = vec![super::DisplayDataAddress::ROW_0.bits()];
+        base.extend([0; super::ROWS_SIZE].iter().cloned());
+
+        let expectations = [
+            I2cTransaction::write(ADDRESS, overlay),
+            I2cTransaction::write(ADDRESS, base),
+        ];
 
         let mut i2c = I2cMock::new(&expectations);
         let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
 
-        ht16k33.set_oscillator(super::Oscillator::OFF).unwrap();
+        ht16k33.set_overlay([DisplayData::all(); ROWS_SIZE], 100);
+        ht16k33.tick().unwrap();
+
+        ht16k33.clear_overlay();
+        assert!(ht16k33.overlay().is_none());
+
+        ht16k33.tick().unwrap();
 
         i2c = ht16k33.destroy();
         i2c.done();
     }
 
     #[test]
-    fn set_display() {
-        let expectations = [I2cTransaction::write(
+    fn overlay_keeps_canary() {
+        let mut ht16k33 = HT16K33::new(crate::i2c_mock::I2cMock::new(), ADDRESS);
+        let canary = LedLocation::new(15, 7).unwrap();
+
+        ht16k33.set_canary(Some(canary));
+        ht16k33.write_display_buffer().unwrap();
+        assert!(ht16k33.validate_state().unwrap());
+
+        // The overlay does not include the canary, it is added when writing.
+        ht16k33.set_overlay([DisplayData::empty(); ROWS_SIZE], 2);
+        ht16k33.tick().unwrap();
+        assert!(ht16k33.overlay().is_some());
+        assert!(ht16k33.validate_state().unwrap());
+    }
+
+    #[test]
+    fn read_key_buffer() {
+        let key_data = vec![0b0000_0001, 0, 0, 0b0000_1000, 0, 0b0001_0000];
+
+        let expectations = [I2cTransaction::write_read(
             ADDRESS,
-            vec![(super::Display::COMMAND | super::Display::OFF).bits()],
+            vec![super::KEY_DATA_ADDRESS],
+            key_data.clone(),
         )];
 
         let mut i2c = I2cMock::new(&expectations);
         let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
 
-        ht16k33.set_display(super::Display::OFF).unwrap();
+        ht16k33.read_key_buffer().unwrap();
+
+        assert_eq!(&ht16k33.key_buffer()[..], &key_data[..]);
 
         i2c = ht16k33.destroy();
         i2c.done();
     }
 
     #[test]
-    fn set_dimming() {
-        let expectations = [I2cTransaction::write(
-            ADDRESS,
-            vec![(super::Dimming::COMMAND | Dimming::BRIGHTNESS_MAX).bits()],
-        )];
+    fn borrowed_i2c() {
+        let mut ram = vec![0u8; ROWS_SIZE + 1];
+        ram[1] = 0b0000_0001;
+
+        let expectations = [
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Oscillator::COMMAND | super::Oscillator::ON).bits()],
+            ),
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Display::COMMAND | super::Display::ON).bits()],
+            ),
+            I2cTransaction::write(
+                ADDRESS,
+                vec![(super::Dimming::COMMAND | super::Dimming::BRIGHTNESS_MIN).bits()],
+            ),
+            I2cTransaction::write(ADDRESS, vec![0, 0b0000_0001]),
+            I2cTransaction::write(ADDRESS, ram),
+            I2cTransaction::write_read(
+                ADDRESS,
+                vec![super::KEY_DATA_ADDRESS],
+                vec![0b0000_0001, 0, 0, 0, 0, 0],
+            ),
+        ];
 
         let mut i2c = I2cMock::new(&expectations);
-        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+        let mut ht16k33 = HT16K33::new((), ADDRESS);
 
-        ht16k33.set_dimming(Dimming::BRIGHTNESS_MAX).unwrap();
+        ht16k33
+            .set_oscillator_with(&mut i2c, super::Oscillator::ON)
+            .unwrap();
+        ht16k33
+            .set_display_with(&mut i2c, super::Display::ON)
+            .unwrap();
+        ht16k33
+            .set_dimming_with(&mut i2c, super::Dimming::BRIGHTNESS_MIN)
+            .unwrap();
+        assert!(ht16k33.is_synced());
+
+        ht16k33
+            .set_led_with(&mut i2c, LedLocation::new(0, 0).unwrap(), true)
+            .unwrap();
+        assert!(ht16k33.needs_flush());
+
+        ht16k33.write_display_buffer_with(&mut i2c).unwrap();
+        assert!(!ht16k33.needs_flush());
+
+        assert!(ht16k33.read_key_data_with(&mut i2c).unwrap().any_pressed());
 
-        i2c = ht16k33.destroy();
         i2c.done();
     }
 
     #[test]
-    fn set_led() {
-        let expectations = [I2cTransaction::write(ADDRESS, vec![1u8, 0b1000_0000])];
+    fn borrowed_i2c_auto_wake() {
+        let mut i2c = crate::i2c_mock::I2cMock::new();
+        let mut ht16k33 = HT16K33::new((), ADDRESS);
+        ht16k33.set_auto_wake(true);
 
-        let mut i2c = I2cMock::new(&expectations);
-        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+        let standby = |ht16k33: &mut HT16K33<()>, i2c: &mut crate::i2c_mock::I2cMock| {
+            ht16k33.set_oscillator_with(i2c, Oscillator::OFF).unwrap();
+            assert_eq!(Oscillator::OFF, i2c.oscillator);
+        };
 
+        standby(&mut ht16k33, &mut i2c);
+        ht16k33.set_display_with(&mut i2c, Display::ON).unwrap();
+        assert_eq!(Oscillator::ON, i2c.oscillator);
+
+        standby(&mut ht16k33, &mut i2c);
         ht16k33
-            .set_led(LedLocation::new(1, 7).unwrap(), true)
+            .set_led_with(&mut i2c, LedLocation::new(0, 0).unwrap(), true)
             .unwrap();
+        assert_eq!(Oscillator::ON, i2c.oscillator);
 
-        i2c = ht16k33.destroy();
-        i2c.done();
+        standby(&mut ht16k33, &mut i2c);
+        ht16k33.write_display_buffer_with(&mut i2c).unwrap();
+        assert_eq!(Oscillator::ON, i2c.oscillator);
+
+        standby(&mut ht16k33, &mut i2c);
+        ht16k33.read_key_data_with(&mut i2c).unwrap();
+        assert_eq!(Oscillator::ON, i2c.oscillator);
+        assert_eq!(PowerState::Normal, ht16k33.power_state());
+
+        // Turning the display off does not wake the chip.
+        standby(&mut ht16k33, &mut i2c);
+        ht16k33.set_display_with(&mut i2c, Display::OFF).unwrap();
+        assert_eq!(Oscillator::OFF, i2c.oscillator);
     }
 
     #[test]
-    fn write_display_buffer() {
-        let mut write_buffer = vec![super::DisplayDataAddress::ROW_0.bits()];
-        write_buffer.extend([0; super::ROWS_SIZE].iter().cloned());
+    fn borrowed_i2c_shared_bus() {
+        let mut i2c = crate::i2c_mock::I2cMock::new();
 
-        let expectations = [I2cTransaction::write(ADDRESS, write_buffer)];
+        let mut first = HT16K33::new((), 1);
+        let mut second = HT16K33::new((), 2);
+
+        first.set_dimming_supported(false);
+        first
+            .set_dimming_with(&mut i2c, super::Dimming::BRIGHTNESS_MIN)
+            .unwrap();
+        assert_eq!(super::Dimming::BRIGHTNESS_MAX, i2c.dimming);
+
+        first
+            .set_led_with(&mut i2c, LedLocation::new(3, 2).unwrap(), true)
+            .unwrap();
+        second
+            .set_led_with(&mut i2c, LedLocation::new(4, 5).unwrap(), true)
+            .unwrap();
+
+        // The mock ignores the address, so both chips write to the same display RAM.
+        assert_eq!(0b0000_0100, i2c.data_values[3]);
+        assert_eq!(0b0010_0000, i2c.data_values[4]);
+    }
+
+    #[test]
+    fn read_key_data() {
+        let key_data = vec![0b0000_0001, 0, 0, 0b0000_1000, 0, 0b0001_0000];
+
+        let expectations = [I2cTransaction::write_read(
+            ADDRESS,
+            vec![super::KEY_DATA_ADDRESS],
+            key_data.clone(),
+        )];
 
         let mut i2c = I2cMock::new(&expectations);
         let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
 
-        ht16k33.write_display_buffer().unwrap();
+        let data = ht16k33.read_key_data().unwrap();
+
+        assert_eq!(&data.bytes()[..], &key_data[..]);
+        assert_eq!(data.bytes(), ht16k33.key_buffer());
 
         i2c = ht16k33.destroy();
         i2c.done();
     }
 
+    #[test]
+    fn read_key_buffer_clears_int_flag() {
+        let mut i2c = crate::i2c_mock::I2cMock::new();
+        i2c.int_flag = 0xFF;
+        i2c.key_values[2] = 0b0000_0100;
+
+        let mut ht16k33 = HT16K33::new(i2c, ADDRESS);
+
+        ht16k33.read_key_buffer().unwrap();
+
+        assert_eq!(ht16k33.key_buffer()[2], 0b0000_0100);
+
+        // The INT flag was cleared by the read, so there are no new key changes.
+        assert!(!ht16k33.keys_changed_since_last_poll(0).unwrap());
+
+        let i2c = ht16k33.destroy();
+        assert_eq!(i2c.int_flag, 0);
+    }
+
     #[test]
     fn read_display_buffer() {
         let mut read_buffer = vec![0; super::ROWS_SIZE];
@@ -0,0 +1,288 @@
+use crate::constants::{COMMONS_SIZE, ROWS_SIZE};
+use crate::types::DisplayData;
+
+/// How [`Life`] treats the cells beyond the edges of the frame.
+///
+/// [`Life`]: struct.Life.html
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Edges {
+    /// The frame wraps around, the first and last ROWs (and COMMONs) are neighbors.
+    Torus,
+    /// The cells beyond the edges are always dead.
+    Bounded,
+}
+
+/// Conway's Game of Life on a frame of `ROWS_SIZE` ROWs by `COMMONS_SIZE` COMMONs, one cell per LED.
+///
+/// # Examples
+///
+/// ```
+/// use ht16k33::{DisplayData, Edges, Life, ROWS_SIZE};
+///
+/// let life = Life::new(Edges::Torus);
+///
+/// let mut frame = [DisplayData::empty(); ROWS_SIZE];
+/// Life::seed_glider(&mut frame, 0, 0);
+///
+/// // A glider on a torus returns to its shape, moved by one cell diagonally, every 4 generations.
+/// let mut next = frame;
+/// for _ in 0..4 {
+///     next = life.step(&next);
+/// }
+///
+/// let mut moved = [DisplayData::empty(); ROWS_SIZE];
+/// Life::seed_glider(&mut moved, 1, 1);
+///
+/// assert_eq!(moved, next);
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Life {
+    edges: Edges,
+}
+
+impl Life {
+    /// Create a Game of Life engine.
+    ///
+    /// # Arguments
+    ///
+    /// * `edges` - How the cells beyond the edges of the frame are treated.
+    pub const fn new(edges: Edges) -> Self {
+        Life { edges }
+    }
+
+    /// Return how the cells beyond the edges of the frame are treated.
+    pub fn edges(&self) -> Edges {
+        self.edges
+    }
+
+    /// Return the next generation of the frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The current generation.
+    pub fn step(&self, frame: &[DisplayData; ROWS_SIZE]) -> [DisplayData; ROWS_SIZE] {
+        let mut next = [DisplayData::empty(); ROWS_SIZE];
+
+        for (row, next_row) in next.iter_mut().enumerate() {
+            for common in 0..COMMONS_SIZE {
+                let neighbors = self.neighbors(frame, row, common);
+                let alive = Self::is_alive(frame, row, common);
+
+                if neighbors == 3 || (alive && neighbors == 2) {
+                    next_row.insert(Self::cell(common));
+                }
+            }
+        }
+
+        next
+    }
+
+    /// Return the number of live cells in the frame.
+    pub fn population(frame: &[DisplayData; ROWS_SIZE]) -> u32 {
+        crate::frame::count_set(frame)
+    }
+
+    /// Add a glider to the frame, moving towards higher ROWs and COMMONs, with its bounding box starting at the
+    /// given cell. Cells beyond the edges wrap around.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The frame to add the glider to.
+    /// * `row` - The first ROW of the glider's bounding box.
+    /// * `common` - The first COMMON of the glider's bounding box.
+    pub fn seed_glider(frame: &mut [DisplayData; ROWS_SIZE], row: usize, common: usize) {
+        for &(d_row, d_common) in [(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)].iter() {
+            Self::seed(frame, row + d_row, common + d_common);
+        }
+    }
+
+    /// Add a blinker, a period 2 oscillator, to the frame, as 3 cells along the ROWs starting at the given
+    /// cell. Cells beyond the edges wrap around.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The frame to add the blinker to.
+    /// * `row` - The first ROW of the blinker.
+    /// * `common` - The COMMON of the blinker.
+    pub fn seed_blinker(frame: &mut [DisplayData; ROWS_SIZE], row: usize, common: usize) {
+        for d_row in 0..3 {
+            Self::seed(frame, row + d_row, common);
+        }
+    }
+
+    /// Add random live cells to the frame.
+    ///
+    /// The random numbers come from a closure, so any random number generator can be used, e.g.
+    /// `|| rng.next_u32()` for a `rand_core::RngCore`.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The frame to add the cells to.
+    /// * `rng` - Returns uniformly distributed random numbers.
+    /// * `density` - The chance of each cell becoming alive, out of 256.
+    pub fn seed_random<R>(frame: &mut [DisplayData; ROWS_SIZE], mut rng: R, density: u8)
+    where
+        R: FnMut() -> u32,
+    {
+        for row in frame.iter_mut() {
+            for common in 0..COMMONS_SIZE {
+                // Use the high bits, the low bits of simple generators are the least random.
+                if (rng() >> 24) < u32::from(density) {
+                    row.insert(Self::cell(common));
+                }
+            }
+        }
+    }
+
+    fn seed(frame: &mut [DisplayData; ROWS_SIZE], row: usize, common: usize) {
+        frame[row % ROWS_SIZE].insert(Self::cell(common % COMMONS_SIZE));
+    }
+
+    fn cell(common: usize) -> DisplayData {
+        DisplayData::from_bits_truncate(1 << common)
+    }
+
+    fn is_alive(frame: &[DisplayData; ROWS_SIZE], row: usize, common: usize) -> bool {
+        frame[row].contains(Self::cell(common))
+    }
+
+    fn neighbors(&self, frame: &[DisplayData; ROWS_SIZE], row: usize, common: usize) -> u8 {
+        let mut neighbors = 0;
+
+        for d_row in [ROWS_SIZE - 1, 0, 1].iter() {
+            for d_common in [COMMONS_SIZE - 1, 0, 1].iter() {
+                if *d_row == 0 && *d_common == 0 {
+                    continue;
+                }
+
+                // Offsets are added modulo the size, so `SIZE - 1` is a step backwards.
+                let (n_row, n_common) = match self.edges {
+                    Edges::Torus => (
+                        (row + d_row) % ROWS_SIZE,
+                        (common + d_common) % COMMONS_SIZE,
+                    ),
+                    Edges::Bounded => {
+                        let n_row = (row + d_row) % ROWS_SIZE;
+                        let n_common = (common + d_common) % COMMONS_SIZE;
+
+                        // Skip the neighbors that wrapped around an edge.
+                        if n_row > row + 1
+                            || row > n_row + 1
+                            || n_common > common + 1
+                            || common > n_common + 1
+                        {
+                            continue;
+                        }
+
+                        (n_row, n_common)
+                    }
+                };
+
+                if Self::is_alive(frame, n_row, n_common) {
+                    neighbors += 1;
+                }
+            }
+        }
+
+        neighbors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMPTY: [DisplayData; ROWS_SIZE] = [DisplayData::empty(); ROWS_SIZE];
+
+    #[test]
+    fn empty() {
+        assert_eq!(EMPTY, Life::new(Edges::Torus).step(&EMPTY));
+        assert_eq!(EMPTY, Life::new(Edges::Bounded).step(&EMPTY));
+    }
+
+    #[test]
+    fn block_is_still() {
+        let mut frame = EMPTY;
+        frame[3] = DisplayData::COMMON_4 | DisplayData::COMMON_5;
+        frame[4] = DisplayData::COMMON_4 | DisplayData::COMMON_5;
+
+        assert_eq!(frame, Life::new(Edges::Bounded).step(&frame));
+    }
+
+    #[test]
+    fn blinker() {
+        let life = Life::new(Edges::Bounded);
+
+        let mut frame = EMPTY;
+        Life::seed_blinker(&mut frame, 4, 3);
+
+        let next = life.step(&frame);
+
+        let mut expected = EMPTY;
+        expected[5] = DisplayData::COMMON_2 | DisplayData::COMMON_3 | DisplayData::COMMON_4;
+        assert_eq!(expected, next);
+
+        assert_eq!(frame, life.step(&next));
+    }
+
+    #[test]
+    fn edges() {
+        // A blinker across the COMMON 7 to COMMON 0 edge.
+        let mut frame = EMPTY;
+        frame[5] = DisplayData::COMMON_6 | DisplayData::COMMON_7 | DisplayData::COMMON_0;
+
+        // On a torus it keeps blinking.
+        let torus = Life::new(Edges::Torus);
+        let mut expected = EMPTY;
+        Life::seed_blinker(&mut expected, 4, 7);
+        assert_eq!(expected, torus.step(&frame));
+
+        // Bounded, the cells on either side of the edge are not neighbors, so they all die.
+        let bounded = Life::new(Edges::Bounded);
+        assert_eq!(EMPTY, bounded.step(&frame));
+    }
+
+    #[test]
+    fn glider_wraps() {
+        let life = Life::new(Edges::Torus);
+
+        let mut frame = EMPTY;
+        Life::seed_glider(&mut frame, 0, 0);
+
+        // The glider crosses all the ROWs and COMMONs, and returns to its starting position.
+        let mut next = frame;
+        for _ in 0..4 * ROWS_SIZE {
+            next = life.step(&next);
+            assert_eq!(5, Life::population(&next));
+        }
+
+        assert_eq!(frame, next);
+    }
+
+    #[test]
+    fn glider_bounded() {
+        let life = Life::new(Edges::Bounded);
+
+        let mut frame = EMPTY;
+        Life::seed_glider(&mut frame, 0, 0);
+
+        // The glider hits the COMMON edge, and settles as a block.
+        for _ in 0..4 * COMMONS_SIZE {
+            frame = life.step(&frame);
+        }
+
+        assert_eq!(4, Life::population(&frame));
+        assert_eq!(frame, life.step(&frame));
+    }
+
+    #[test]
+    fn seed_random() {
+        let mut frame = EMPTY;
+        Life::seed_random(&mut frame, || u32::MAX, u8::MAX);
+        assert_eq!(EMPTY, frame);
+
+        let mut values = [0u32, u32::MAX].iter().cycle().cloned();
+        Life::seed_random(&mut frame, || values.next().unwrap(), 1);
+        assert_eq!(64, Life::population(&frame));
+    }
+}
@@ -0,0 +1,476 @@
+use crate::constants::{KEY_DATA_ADDRESS, KEY_DATA_SIZE};
+use crate::state::Ht16k33State;
+use crate::types::{
+    DeviceDefaults, Dimming, Display, DisplayData, DisplayDataAddress, KeyData, LedLocation,
+    Oscillator,
+};
+
+use embedded_hal::blocking::i2c::{Write, WriteRead};
+
+/// A minimal HT16K33 driver without any cached state.
+///
+/// Unlike [`HT16K33`], the driver keeps no display buffer, key buffer, or register state, only the I2C device
+/// and address. Every [set_led()] is a read-modify-write of a single display RAM row, and whole rows can be
+/// written directly with [write_row()]. The setup registers are write-only on the chip, so their state is not
+/// tracked at all.
+///
+/// Use this when RAM is very tight and the extra I2C traffic is acceptable. The board wiring options of
+/// [`HT16K33`], e.g. the row map and bit order, are not supported.
+///
+/// # Examples
+///
+/// ```
+/// # use failure::Error;
+/// # use ht16k33::i2c_mock::I2cMock;
+/// use ht16k33::{Ht16k33Lite, LedLocation};
+/// # fn main() -> Result<(), Error> {
+/// # let mut i2c = I2cMock::new();
+/// # let address = 0u8;
+///
+/// let mut ht16k33 = Ht16k33Lite::new(i2c, address);
+/// ht16k33.initialize()?;
+///
+/// ht16k33.set_led(LedLocation::new(0, 0)?, true)?;
+///
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`HT16K33`]: struct.HT16K33.html
+/// [set_led()]: struct.Ht16k33Lite.html#method.set_led
+/// [write_row()]: struct.Ht16k33Lite.html#method.write_row
+pub struct Ht16k33Lite<I2C> {
+    i2c: I2C,
+
+    // Device I2C address.
+    address: u8,
+}
+
+impl<I2C, E> Ht16k33Lite<I2C>
+where
+    I2C: Write<Error = E> + WriteRead<Error = E>,
+{
+    /// Create a minimal HT16K33 driver.
+    ///
+    /// # Arguments
+    ///
+    /// * `i2c` - The I2C device to communicate with the HT16K33 chip.
+    /// * `address` - The I2C device address.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// use ht16k33::Ht16k33Lite;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = Ht16k33Lite::new(i2c, address);
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub const fn new(i2c: I2C, address: u8) -> Self {
+        Ht16k33Lite { i2c, address }
+    }
+
+    /// Return the given I2C device, making this device unusable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// use ht16k33::Ht16k33Lite;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = Ht16k33Lite::new(i2c, address);
+    ///
+    /// let i2c = ht16k33.destroy();
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn destroy(self) -> I2C {
+        self.i2c
+    }
+
+    /// Initialize the HT16K33.
+    ///
+    /// Enables the oscillator, sets the display and dimming to their power-on defaults, and clears the display
+    /// RAM, like [`HT16K33::initialize()`].
+    ///
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// use ht16k33::Ht16k33Lite;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = Ht16k33Lite::new(i2c, address);
+    /// ht16k33.initialize()?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`HT16K33::initialize()`]: struct.HT16K33.html#method.initialize
+    pub fn initialize(&mut self) -> Result<(), E> {
+        self.set_oscillator(Oscillator::ON)?;
+        self.set_display(DeviceDefaults::POWER_ON.display)?;
+        self.set_dimming(DeviceDefaults::POWER_ON.dimming)?;
+        self.clear()?;
+
+        Ok(())
+    }
+
+    /// Control the oscillator.
+    ///
+    /// # Arguments
+    ///
+    /// * `oscillator` - Set the oscillator On/Off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// use ht16k33::{Ht16k33Lite, Oscillator};
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = Ht16k33Lite::new(i2c, address);
+    /// ht16k33.set_oscillator(Oscillator::ON)?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_oscillator(&mut self, oscillator: Oscillator) -> Result<(), E> {
+        self.i2c
+            .write(self.address, &Ht16k33State::encode_system_setup(oscillator))
+    }
+
+    /// Control the display.
+    ///
+    /// # Arguments
+    ///
+    /// * `display` - Set the display On/Off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// use ht16k33::{Display, Ht16k33Lite};
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = Ht16k33Lite::new(i2c, address);
+    /// ht16k33.set_display(Display::HALF_HZ)?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_display(&mut self, display: Display) -> Result<(), E> {
+        self.i2c
+            .write(self.address, &Ht16k33State::encode_display_setup(display))
+    }
+
+    /// Control the display dimming.
+    ///
+    /// # Arguments
+    ///
+    /// * `dimming` - Set the dimming brightness.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// use ht16k33::{Dimming, Ht16k33Lite};
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = Ht16k33Lite::new(i2c, address);
+    /// ht16k33.set_dimming(Dimming::from_u8(4)?)?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_dimming(&mut self, dimming: Dimming) -> Result<(), E> {
+        self.i2c
+            .write(self.address, &Ht16k33State::encode_dimming_set(dimming))
+    }
+
+    /// Control an LED, by reading its row from the display RAM and writing it back.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - The LED location to update.
+    /// * `enabled` - Set the LED on (true) or off (false).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// use ht16k33::{Ht16k33Lite, LedLocation};
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = Ht16k33Lite::new(i2c, address);
+    ///
+    /// let location = LedLocation::new(0, 0)?;
+    /// ht16k33.set_led(location, true)?;
+    ///
+    /// assert!(ht16k33.read_led(location)?);
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_led(&mut self, location: LedLocation, enabled: bool) -> Result<(), E> {
+        let mut data = self.read_row(location.row)?;
+
+        data.set(location.common, enabled);
+
+        self.write_row(location.row, data)
+    }
+
+    /// Return whether an LED is on, read from the display RAM.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - The LED location to read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// use ht16k33::{Ht16k33Lite, LedLocation};
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = Ht16k33Lite::new(i2c, address);
+    ///
+    /// if ht16k33.read_led(LedLocation::new(0, 0)?)? {
+    ///     // The LED is on.
+    /// }
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_led(&mut self, location: LedLocation) -> Result<bool, E> {
+        Ok(self.read_row(location.row)?.contains(location.common))
+    }
+
+    /// Write a whole row of the display RAM, without reading it first.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - The display RAM row to write.
+    /// * `data` - The common data of the row.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// use ht16k33::{DisplayData, DisplayDataAddress, Ht16k33Lite};
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = Ht16k33Lite::new(i2c, address);
+    ///
+    /// ht16k33.write_row(DisplayDataAddress::ROW_3, DisplayData::COMMON_0 | DisplayData::COMMON_7)?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_row(&mut self, row: DisplayDataAddress, data: DisplayData) -> Result<(), E> {
+        self.i2c.write(self.address, &[row.bits(), data.bits()])
+    }
+
+    /// Read a whole row of the display RAM.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - The display RAM row to read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// use ht16k33::{DisplayData, DisplayDataAddress, Ht16k33Lite};
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = Ht16k33Lite::new(i2c, address);
+    ///
+    /// ht16k33.write_row(DisplayDataAddress::ROW_3, DisplayData::COMMON_7)?;
+    /// assert_eq!(DisplayData::COMMON_7, ht16k33.read_row(DisplayDataAddress::ROW_3)?);
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_row(&mut self, row: DisplayDataAddress) -> Result<DisplayData, E> {
+        let mut data = [0u8; 1];
+
+        self.i2c
+            .write_read(self.address, &[row.bits()], &mut data)?;
+
+        Ok(DisplayData::from_bits_truncate(data[0]))
+    }
+
+    /// Clear the whole display RAM in a single write.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// use ht16k33::Ht16k33Lite;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = Ht16k33Lite::new(i2c, address);
+    /// ht16k33.clear()?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn clear(&mut self) -> Result<(), E> {
+        // An empty state encodes a blank display RAM.
+        self.i2c
+            .write(self.address, &Ht16k33State::new().display_ram_bytes())
+    }
+
+    /// Read the key data RAM from the HT16K33 chip, and return it as [`KeyData`].
+    ///
+    /// This clears the INT flag on the chip.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use failure::Error;
+    /// # use ht16k33::i2c_mock::I2cMock;
+    /// use ht16k33::Ht16k33Lite;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut i2c = I2cMock::new();
+    /// # let address = 0u8;
+    ///
+    /// let mut ht16k33 = Ht16k33Lite::new(i2c, address);
+    ///
+    /// if ht16k33.read_key_data()?.any_pressed() {
+    ///     // Handle the key press.
+    /// }
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`KeyData`]: struct.KeyData.html
+    pub fn read_key_data(&mut self) -> Result<KeyData, E> {
+        let mut key_data = [0u8; KEY_DATA_SIZE];
+
+        self.i2c
+            .write_read(self.address, &[KEY_DATA_ADDRESS], &mut key_data)?;
+
+        Ok(KeyData::new(key_data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::DISPLAY_RAM_BYTES;
+    use crate::i2c_mock::I2cMock;
+
+    const ADDRESS: u8 = 0;
+
+    #[test]
+    fn size() {
+        assert_eq!(
+            core::mem::size_of::<I2cMock>() + core::mem::size_of::<u8>(),
+            core::mem::size_of::<Ht16k33Lite<I2cMock>>()
+        );
+    }
+
+    #[test]
+    fn initialize() {
+        let mut i2c = I2cMock::new();
+        i2c.data_values = [0xFF; DISPLAY_RAM_BYTES];
+
+        let mut ht16k33 = Ht16k33Lite::new(i2c, ADDRESS);
+        ht16k33.initialize().unwrap();
+
+        let i2c = ht16k33.destroy();
+        assert_eq!(Oscillator::ON, i2c.oscillator);
+        assert_eq!(DeviceDefaults::POWER_ON.display, i2c.display);
+        assert_eq!(DeviceDefaults::POWER_ON.dimming, i2c.dimming);
+        assert_eq!([0; DISPLAY_RAM_BYTES], i2c.data_values);
+    }
+
+    #[test]
+    fn set_led() {
+        let mut ht16k33 = Ht16k33Lite::new(I2cMock::new(), ADDRESS);
+
+        let first = LedLocation::new(2, 1).unwrap();
+        let second = LedLocation::new(2, 6).unwrap();
+
+        ht16k33.set_led(first, true).unwrap();
+        ht16k33.set_led(second, true).unwrap();
+        assert!(ht16k33.read_led(first).unwrap());
+        assert!(ht16k33.read_led(second).unwrap());
+
+        ht16k33.set_led(first, false).unwrap();
+        assert!(!ht16k33.read_led(first).unwrap());
+        assert!(ht16k33.read_led(second).unwrap());
+
+        assert_eq!(0b0100_0000, ht16k33.destroy().data_values[2]);
+    }
+
+    #[test]
+    fn write_row() {
+        let mut ht16k33 = Ht16k33Lite::new(I2cMock::new(), ADDRESS);
+
+        ht16k33
+            .write_row(DisplayDataAddress::ROW_15, DisplayData::all())
+            .unwrap();
+
+        assert_eq!(
+            DisplayData::all(),
+            ht16k33.read_row(DisplayDataAddress::ROW_15).unwrap()
+        );
+        assert_eq!(0xFF, ht16k33.destroy().data_values[15]);
+    }
+
+    #[test]
+    fn read_key_data() {
+        let mut i2c = I2cMock::new();
+        i2c.oscillator = Oscillator::ON;
+        i2c.scan_keys([0, 0b0000_0010, 0, 0, 0, 0]);
+
+        let mut ht16k33 = Ht16k33Lite::new(i2c, ADDRESS);
+
+        assert!(ht16k33.read_key_data().unwrap().any_pressed());
+        assert_eq!(0, ht16k33.destroy().int_flag);
+    }
+}
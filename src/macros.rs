@@ -0,0 +1,55 @@
+/// Create an array of [`LedLocation`]s from `(row, common)` pairs, validated at compile time.
+///
+/// Each pair must be a constant expression; out-of-range values are a compile error rather than a runtime
+/// [`ValidationError`]. Useful for defining static icons and layouts.
+///
+/// # Example
+///
+/// ```
+/// use ht16k33::leds;
+/// use ht16k33::LedLocation;
+///
+/// const ICON: [LedLocation; 3] = leds![(0, 0), (1, 2), (3, 4)];
+///
+/// assert_eq!(LedLocation::new(1, 2).unwrap(), ICON[1]);
+/// ```
+///
+/// ```compile_fail
+/// use ht16k33::leds;
+///
+/// // The `common` value is out of range.
+/// let icon = leds![(0, 0), (1, 8)];
+/// ```
+///
+/// [`LedLocation`]: struct.LedLocation.html
+/// [`ValidationError`]: enum.ValidationError.html
+#[macro_export]
+macro_rules! leds {
+    ($(($row:expr, $common:expr)),* $(,)?) => {
+        [$({
+            const LOCATION: $crate::LedLocation = $crate::LedLocation::new_const($row, $common);
+            LOCATION
+        }),*]
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::LedLocation;
+
+    #[test]
+    fn leds() {
+        let locations = leds![(0, 0), (1, 2), (15, 7)];
+
+        assert_eq!(LedLocation::new(0, 0).unwrap(), locations[0]);
+        assert_eq!(LedLocation::new(1, 2).unwrap(), locations[1]);
+        assert_eq!(LedLocation::new(15, 7).unwrap(), locations[2]);
+    }
+
+    #[test]
+    fn leds_empty() {
+        let locations: [LedLocation; 0] = leds![];
+
+        assert!(locations.is_empty());
+    }
+}
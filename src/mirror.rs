@@ -0,0 +1,213 @@
+use crate::constants::ROWS_SIZE;
+use crate::types::DisplayData;
+
+use embedded_hal::blocking::i2c::{Write, WriteRead};
+
+/// Receives every frame written to the HT16K33 display RAM through a [`Mirror`].
+///
+/// Implemented for closures taking the frame, e.g. to forward it over the network for remote debugging.
+///
+/// [`Mirror`]: struct.Mirror.html
+pub trait MirrorSink {
+    /// Called after each successful write to the display RAM, with the resulting display RAM contents.
+    fn frame(&mut self, frame: &[DisplayData; ROWS_SIZE]);
+}
+
+impl<F> MirrorSink for F
+where
+    F: FnMut(&[DisplayData; ROWS_SIZE]),
+{
+    fn frame(&mut self, frame: &[DisplayData; ROWS_SIZE]) {
+        self(frame)
+    }
+}
+
+/// Wraps an I2C device to forward everything written to the display RAM to a [`MirrorSink`].
+///
+/// The wrapper is passed to [`HT16K33::new`] in place of the I2C device, so the application's render code is
+/// unchanged. It keeps a copy of the display RAM, updated from the data written to the display RAM addresses
+/// (including address wrap-around), and passes it to the sink after each write.
+///
+/// *NOTE: If the write length is limited with `set_max_write_len()`, each flush is split into several writes
+/// and the sink is called for each of them.*
+///
+/// # Examples
+///
+/// ```
+/// # use failure::Error;
+/// # use ht16k33::i2c_mock::I2cMock;
+/// use ht16k33::{DisplayData, LedLocation, Mirror, HT16K33, ROWS_SIZE};
+/// # fn main() -> Result<(), Error> {
+/// # let mut i2c = I2cMock::new();
+/// # let address = 0u8;
+///
+/// let mut frames = 0;
+/// let mirror = Mirror::new(i2c, |frame: &[DisplayData; ROWS_SIZE]| {
+///     // Send the frame somewhere.
+///     frames += 1;
+/// });
+///
+/// let mut ht16k33 = HT16K33::new(mirror, address);
+///
+/// ht16k33.set_led(LedLocation::new(1, 2)?, true)?;
+///
+/// ht16k33.destroy();
+/// assert_eq!(1, frames);
+///
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`MirrorSink`]: trait.MirrorSink.html
+/// [`HT16K33::new`]: struct.HT16K33.html#method.new
+pub struct Mirror<I2C, S> {
+    i2c: I2C,
+    sink: S,
+    frame: [DisplayData; ROWS_SIZE],
+}
+
+impl<I2C, S> Mirror<I2C, S>
+where
+    S: MirrorSink,
+{
+    /// Wrap the I2C device, forwarding display RAM writes to the sink.
+    ///
+    /// # Arguments
+    ///
+    /// * `i2c` - The I2C device to communicate with the HT16K33 chip.
+    /// * `sink` - The sink to forward frames to.
+    pub fn new(i2c: I2C, sink: S) -> Self {
+        Mirror {
+            i2c,
+            sink,
+            frame: [DisplayData::empty(); ROWS_SIZE],
+        }
+    }
+
+    /// Return the mirrored display RAM contents.
+    pub fn frame(&self) -> &[DisplayData; ROWS_SIZE] {
+        &self.frame
+    }
+
+    /// Return the I2C device and the sink.
+    pub fn into_inner(self) -> (I2C, S) {
+        (self.i2c, self.sink)
+    }
+}
+
+impl<I2C, S> Write for Mirror<I2C, S>
+where
+    I2C: Write,
+    S: MirrorSink,
+{
+    type Error = I2C::Error;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.i2c.write(address, bytes)?;
+
+        // Display RAM writes start with a display data address, followed by at least one byte of data.
+        if let Some((&row, data)) = bytes.split_first() {
+            if (row as usize) < ROWS_SIZE && !data.is_empty() {
+                for (offset, &value) in data.iter().enumerate() {
+                    self.frame[(row as usize + offset) % ROWS_SIZE] =
+                        DisplayData::from_bits_truncate(value);
+                }
+
+                self.sink.frame(&self.frame);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<I2C, S> WriteRead for Mirror<I2C, S>
+where
+    I2C: WriteRead,
+{
+    type Error = I2C::Error;
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.i2c.write_read(address, bytes, buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i2c_mock::I2cMock;
+    use crate::{Display, LedLocation, HT16K33};
+
+    const ADDRESS: u8 = 0;
+
+    #[derive(Default)]
+    struct Frames {
+        count: usize,
+        last: [DisplayData; ROWS_SIZE],
+    }
+
+    impl MirrorSink for Frames {
+        fn frame(&mut self, frame: &[DisplayData; ROWS_SIZE]) {
+            self.count += 1;
+            self.last = *frame;
+        }
+    }
+
+    #[test]
+    fn mirror() {
+        let mirror = Mirror::new(I2cMock::new(), Frames::default());
+        let mut ht16k33 = HT16K33::new(mirror, ADDRESS);
+
+        // Commands are not frames.
+        ht16k33.initialize().unwrap();
+        ht16k33.set_display(Display::ON).unwrap();
+
+        ht16k33.update_display_buffer(LedLocation::new(1, 2).unwrap(), true);
+        ht16k33.update_display_buffer(LedLocation::new(15, 7).unwrap(), true);
+        ht16k33.write_display_buffer().unwrap();
+
+        // Reads are not frames.
+        ht16k33.read_display_buffer().unwrap();
+
+        assert_eq!(ht16k33.display_buffer(), ht16k33.i2c.frame());
+
+        let (i2c, frames) = ht16k33.destroy().into_inner();
+
+        // Initialization clears the display.
+        assert_eq!(2, frames.count);
+
+        for (row, data) in frames.last.iter().enumerate() {
+            assert_eq!(i2c.data_values[row], data.bits());
+        }
+    }
+
+    #[test]
+    fn mirror_split_writes() {
+        let mirror = Mirror::new(I2cMock::new(), Frames::default());
+        let mut ht16k33 = HT16K33::new(mirror, ADDRESS);
+
+        ht16k33.set_max_write_len(9);
+        ht16k33.update_display_buffer(LedLocation::new(15, 7).unwrap(), true);
+        ht16k33.write_display_buffer().unwrap();
+
+        let (_, frames) = ht16k33.destroy().into_inner();
+
+        assert_eq!(2, frames.count);
+        assert_eq!(DisplayData::COMMON_7, frames.last[15]);
+    }
+
+    #[test]
+    fn mirror_wraps_around() {
+        let mut mirror = Mirror::new(I2cMock::new(), Frames::default());
+
+        mirror.write(ADDRESS, &[15, 0x01, 0x02]).unwrap();
+
+        assert_eq!(DisplayData::from_bits_truncate(0x01), mirror.frame()[15]);
+        assert_eq!(DisplayData::from_bits_truncate(0x02), mirror.frame()[0]);
+    }
+}
@@ -0,0 +1,277 @@
+use crate::timing;
+use crate::HT16K33;
+
+use embedded_hal::blocking::i2c::{Write, WriteRead};
+
+/// Statistics about the frames rendered by a [`Renderer`].
+///
+/// [`Renderer`]: struct.Renderer.html
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RenderStats {
+    /// The number of frames flushed to the HT16K33 chip.
+    pub flushes: u32,
+    /// The number of intermediate frames that were coalesced into a later flush.
+    pub dropped_frames: u32,
+}
+
+/// Coalesces display buffer changes into at most one flush per frame period.
+///
+/// The application mutates the display buffer as often as it likes, and calls [render()] from its main loop;
+/// pending changes are only written to the HT16K33 chip once the frame period has passed since the last flush,
+/// so chatty application code cannot saturate the I2C bus.
+///
+/// Time is provided by the caller as a free-running millisecond counter, which may wrap around.
+///
+/// # Examples
+///
+/// ```
+/// # use failure::Error;
+/// # use ht16k33::i2c_mock::I2cMock;
+/// use ht16k33::{LedLocation, Renderer, HT16K33};
+/// # fn main() -> Result<(), Error> {
+/// # let mut i2c = I2cMock::new();
+/// # let address = 0u8;
+///
+/// let mut ht16k33 = HT16K33::new(i2c, address);
+/// let mut renderer = Renderer::new(Renderer::DEFAULT_FRAME_PERIOD_MS);
+///
+/// for now_ms in 0..100 {
+///     let led = LedLocation::new((now_ms % 16) as u8, 0)?;
+///     ht16k33.update_display_buffer(led, true);
+///
+///     renderer.render(&mut ht16k33, now_ms)?;
+/// }
+///
+/// assert_eq!(4, renderer.stats().flushes);
+///
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [render()]: struct.Renderer.html#method.render
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Renderer {
+    frame_period_ms: u32,
+    last_flush_ms: Option<u32>,
+    stats: RenderStats,
+}
+
+impl Renderer {
+    /// The default frame period, for a frame rate of ~30 Hz.
+    ///
+    /// The period is computed with [`for_bus_budget()`] so that full flushes at [`STANDARD_MODE_HZ`] use
+    /// at most 5% of the I2C bus.
+    ///
+    /// [`for_bus_budget()`]: struct.Renderer.html#method.for_bus_budget
+    /// [`STANDARD_MODE_HZ`]: timing/constant.STANDARD_MODE_HZ.html
+    pub const DEFAULT_FRAME_PERIOD_MS: u32 =
+        Renderer::for_bus_budget(timing::STANDARD_MODE_HZ, 5).frame_period_ms;
+
+    /// Create a new renderer that flushes at most once every `frame_period_ms`.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_period_ms` - The minimum number of milliseconds between flushes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ht16k33::Renderer;
+    ///
+    /// // Render at ~60 Hz.
+    /// let renderer = Renderer::new(16);
+    /// ```
+    pub const fn new(frame_period_ms: u32) -> Self {
+        Renderer {
+            frame_period_ms,
+            last_flush_ms: None,
+            stats: RenderStats {
+                flushes: 0,
+                dropped_frames: 0,
+            },
+        }
+    }
+
+    /// Create a new renderer whose full flushes use at most `max_bus_percent` of the I2C bus time.
+    ///
+    /// The frame period is computed with [`timing::full_flush_us`], and is never shorter than 1 ms. A
+    /// `max_bus_percent` of `0` is treated as `1`.
+    ///
+    /// # Arguments
+    ///
+    /// * `bus_hz` - The I2C bus speed, in Hz.
+    /// * `max_bus_percent` - The maximum share of the I2C bus time used by flushes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ht16k33::{timing, Renderer};
+    ///
+    /// let renderer = Renderer::for_bus_budget(timing::FAST_MODE_HZ, 10);
+    ///
+    /// assert_eq!(5, renderer.frame_period_ms());
+    /// ```
+    ///
+    /// [`timing::full_flush_us`]: timing/fn.full_flush_us.html
+    pub const fn for_bus_budget(bus_hz: u32, max_bus_percent: u8) -> Self {
+        let max_bus_percent = if max_bus_percent == 0 {
+            1
+        } else {
+            max_bus_percent as u64
+        };
+
+        let period_us = timing::full_flush_us(bus_hz) as u64 * 100 / max_bus_percent;
+        let period_ms = (period_us + 999) / 1_000;
+
+        Renderer::new(if period_ms > u32::MAX as u64 {
+            u32::MAX
+        } else {
+            period_ms as u32
+        })
+    }
+
+    /// Return the minimum number of milliseconds between flushes.
+    pub fn frame_period_ms(&self) -> u32 {
+        self.frame_period_ms
+    }
+
+    /// Return the render statistics since the renderer was created or the statistics were last reset.
+    pub fn stats(&self) -> RenderStats {
+        self.stats
+    }
+
+    /// Reset the render statistics.
+    pub fn reset_stats(&mut self) {
+        self.stats = RenderStats::default();
+    }
+
+    /// Flush the display buffer to the HT16K33 chip, if it has changed and the frame period has passed.
+    ///
+    /// Returns whether the display buffer was flushed. A call with pending changes that is within the frame
+    /// period is counted as a dropped frame, the changes are flushed by a later call instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `ht16k33` - The driver to flush.
+    /// * `now_ms` - The current time, in milliseconds.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I2C error if the display buffer cannot be written, the changes remain pending.
+    pub fn render<I2C, E>(&mut self, ht16k33: &mut HT16K33<I2C>, now_ms: u32) -> Result<bool, E>
+    where
+        I2C: Write<Error = E> + WriteRead<Error = E>,
+    {
+        if !ht16k33.needs_flush() {
+            return Ok(false);
+        }
+
+        if let Some(last_flush_ms) = self.last_flush_ms {
+            if now_ms.wrapping_sub(last_flush_ms) < self.frame_period_ms {
+                self.stats.dropped_frames = self.stats.dropped_frames.saturating_add(1);
+                return Ok(false);
+            }
+        }
+
+        ht16k33.write_dirty()?;
+
+        self.last_flush_ms = Some(now_ms);
+        self.stats.flushes = self.stats.flushes.saturating_add(1);
+
+        Ok(true)
+    }
+}
+
+impl Default for Renderer {
+    fn default() -> Renderer {
+        Renderer::new(Renderer::DEFAULT_FRAME_PERIOD_MS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i2c_mock::I2cMock;
+    use crate::LedLocation;
+
+    const ADDRESS: u8 = 0;
+
+    #[test]
+    fn default() {
+        assert_eq!(
+            Renderer::DEFAULT_FRAME_PERIOD_MS,
+            Renderer::default().frame_period_ms(),
+            "Renderer default is DEFAULT_FRAME_PERIOD_MS"
+        );
+    }
+
+    #[test]
+    fn default_bus_budget() {
+        assert_eq!(33, Renderer::DEFAULT_FRAME_PERIOD_MS);
+    }
+
+    #[test]
+    fn for_bus_budget() {
+        assert_eq!(
+            33,
+            Renderer::for_bus_budget(timing::STANDARD_MODE_HZ, 5).frame_period_ms()
+        );
+        assert_eq!(
+            1,
+            Renderer::for_bus_budget(timing::FAST_MODE_HZ, 100).frame_period_ms()
+        );
+        assert_eq!(
+            Renderer::for_bus_budget(timing::FAST_MODE_HZ, 1),
+            Renderer::for_bus_budget(timing::FAST_MODE_HZ, 0)
+        );
+    }
+
+    #[test]
+    fn render() {
+        let mut ht16k33 = HT16K33::new(I2cMock::new(), ADDRESS);
+        let mut renderer = Renderer::new(10);
+
+        // The first frame is flushed immediately.
+        assert!(renderer.render(&mut ht16k33, 0).unwrap());
+
+        // Nothing to flush.
+        assert!(!renderer.render(&mut ht16k33, 20).unwrap());
+
+        // Changes within the frame period are coalesced.
+        let led = LedLocation::new(1, 2).unwrap();
+        ht16k33.update_display_buffer(led, true);
+        assert!(renderer.render(&mut ht16k33, 21).unwrap());
+
+        ht16k33.update_display_buffer(led, false);
+        assert!(!renderer.render(&mut ht16k33, 25).unwrap());
+        ht16k33.update_display_buffer(led, true);
+        assert!(!renderer.render(&mut ht16k33, 30).unwrap());
+        assert!(renderer.render(&mut ht16k33, 31).unwrap());
+
+        assert_eq!(
+            RenderStats {
+                flushes: 3,
+                dropped_frames: 2
+            },
+            renderer.stats()
+        );
+
+        let i2c = ht16k33.destroy();
+        assert_eq!(0b0000_0100, i2c.data_values[1]);
+
+        renderer.reset_stats();
+        assert_eq!(RenderStats::default(), renderer.stats());
+    }
+
+    #[test]
+    fn render_wrapping_time() {
+        let mut ht16k33 = HT16K33::new(I2cMock::new(), ADDRESS);
+        let mut renderer = Renderer::new(10);
+
+        assert!(renderer.render(&mut ht16k33, u32::MAX - 5).unwrap());
+
+        ht16k33.update_display_buffer(LedLocation::new(0, 0).unwrap(), true);
+        assert!(!renderer.render(&mut ht16k33, 3).unwrap());
+        assert!(renderer.render(&mut ht16k33, 4).unwrap());
+    }
+}
@@ -0,0 +1,640 @@
+use crate::constants::{DISPLAY_RAM_BYTES, ROWS_SIZE};
+use crate::errors::ValidationError;
+use crate::types::{
+    BitOrder, DeviceDefaults, Dimming, Display, DisplayData, DisplayDataAddress, LedLocation,
+    Oscillator,
+};
+
+// Each display buffer row is written to the same display RAM row.
+pub(crate) const ROW_MAP_IDENTITY: [DisplayDataAddress; ROWS_SIZE] = {
+    let mut row_map = [DisplayDataAddress::ROW_0; ROWS_SIZE];
+
+    let mut row = 0;
+    while row < ROWS_SIZE {
+        row_map[row] = DisplayDataAddress::from_bits_truncate(row as u8);
+        row += 1;
+    }
+
+    row_map
+};
+
+/// The HT16K33 display buffer, board wiring, and setup register state, without any I2C device.
+///
+/// The state produces the exact bytes to write to the chip, e.g. [system_setup_bytes()] and
+/// [display_ram_bytes()], so it can be driven over any transport, and code that only draws into the display
+/// buffer needs no HAL trait bounds. The [`HT16K33`] driver is built on this state, see
+/// [state()](struct.HT16K33.html#method.state).
+///
+/// # Examples
+///
+/// ```
+/// # use ht16k33::ValidationError;
+/// use ht16k33::{Ht16k33State, LedLocation, Oscillator};
+/// # fn main() -> Result<(), ValidationError> {
+///
+/// let mut state = Ht16k33State::new();
+///
+/// state.set_oscillator(Oscillator::ON);
+/// state.update_display_buffer(LedLocation::new(0, 0)?, true);
+///
+/// // Send these with any transport.
+/// let setup = state.system_setup_bytes();
+/// let ram = state.display_ram_bytes();
+///
+/// assert_eq!([0x21], setup);
+/// assert_eq!([0x00, 0x01], ram[..2]);
+///
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [system_setup_bytes()]: struct.Ht16k33State.html#method.system_setup_bytes
+/// [display_ram_bytes()]: struct.Ht16k33State.html#method.display_ram_bytes
+/// [`HT16K33`]: struct.HT16K33.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Ht16k33State {
+    // Represents the desired values of the display RAM, in the logical order.
+    buffer: [DisplayData; ROWS_SIZE],
+
+    // Board wiring, applied when converting between the buffer and the display RAM.
+    bit_order: BitOrder,
+    row_map: [DisplayDataAddress; ROWS_SIZE],
+
+    // The desired values of the write-only setup registers.
+    oscillator: Oscillator,
+    display: Display,
+    dimming: Dimming,
+}
+
+impl Ht16k33State {
+    /// Create the state, matching the chip's power-on defaults and with an empty display buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ht16k33::Ht16k33State;
+    ///
+    /// let state = Ht16k33State::new();
+    ///
+    /// assert_eq!(&ht16k33::DeviceDefaults::POWER_ON.oscillator, state.oscillator());
+    /// ```
+    pub const fn new() -> Self {
+        Ht16k33State {
+            buffer: [DisplayData::empty(); ROWS_SIZE],
+            bit_order: BitOrder::Normal,
+            row_map: ROW_MAP_IDENTITY,
+            oscillator: DeviceDefaults::POWER_ON.oscillator,
+            display: DeviceDefaults::POWER_ON.display,
+            dimming: DeviceDefaults::POWER_ON.dimming,
+        }
+    }
+
+    /// Return the display buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ht16k33::{DisplayData, Ht16k33State, ROWS_SIZE};
+    ///
+    /// let state = Ht16k33State::new();
+    ///
+    /// assert_eq!(&[DisplayData::COMMON_NONE; ROWS_SIZE], state.display_buffer());
+    /// ```
+    pub fn display_buffer(&self) -> &[DisplayData; ROWS_SIZE] {
+        &self.buffer
+    }
+
+    /// Return the display buffer for direct updates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ht16k33::{DisplayData, Ht16k33State};
+    ///
+    /// let mut state = Ht16k33State::new();
+    ///
+    /// state.display_buffer_mut()[0] = DisplayData::all();
+    /// ```
+    pub fn display_buffer_mut(&mut self) -> &mut [DisplayData; ROWS_SIZE] {
+        &mut self.buffer
+    }
+
+    /// Update an LED in the display buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - The LED location to update.
+    /// * `enabled` - Set the LED on (true) or off (false).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ht16k33::ValidationError;
+    /// use ht16k33::{DisplayData, Ht16k33State, LedLocation};
+    /// # fn main() -> Result<(), ValidationError> {
+    ///
+    /// let mut state = Ht16k33State::new();
+    ///
+    /// state.update_display_buffer(LedLocation::new(0, 1)?, true);
+    ///
+    /// assert_eq!(DisplayData::COMMON_1, state.display_buffer()[0]);
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn update_display_buffer(&mut self, location: LedLocation, enabled: bool) {
+        self.buffer[location.row_as_index()].set(location.common, enabled);
+    }
+
+    /// Clear the display buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ht16k33::{DisplayData, Ht16k33State, ROWS_SIZE};
+    ///
+    /// let mut state = Ht16k33State::new();
+    ///
+    /// state.display_buffer_mut()[0] = DisplayData::all();
+    /// state.clear_display_buffer();
+    ///
+    /// assert_eq!(&[DisplayData::COMMON_NONE; ROWS_SIZE], state.display_buffer());
+    /// ```
+    pub fn clear_display_buffer(&mut self) {
+        self.buffer = [DisplayData::COMMON_NONE; ROWS_SIZE];
+    }
+
+    /// Return the oscillator state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ht16k33::{Ht16k33State, Oscillator};
+    ///
+    /// let state = Ht16k33State::new();
+    ///
+    /// assert_eq!(&Oscillator::OFF, state.oscillator());
+    /// ```
+    pub fn oscillator(&self) -> &Oscillator {
+        &self.oscillator
+    }
+
+    /// Set the oscillator state.
+    ///
+    /// # Arguments
+    ///
+    /// * `oscillator` - Set the oscillator On/Off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ht16k33::{Ht16k33State, Oscillator};
+    ///
+    /// let mut state = Ht16k33State::new();
+    ///
+    /// state.set_oscillator(Oscillator::ON);
+    ///
+    /// assert_eq!(&Oscillator::ON, state.oscillator());
+    /// ```
+    pub fn set_oscillator(&mut self, oscillator: Oscillator) {
+        self.oscillator = oscillator;
+    }
+
+    /// Return the display state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ht16k33::{Display, Ht16k33State};
+    ///
+    /// let state = Ht16k33State::new();
+    ///
+    /// assert_eq!(&Display::OFF, state.display());
+    /// ```
+    pub fn display(&self) -> &Display {
+        &self.display
+    }
+
+    /// Set the display state.
+    ///
+    /// # Arguments
+    ///
+    /// * `display` - Set the display On/Off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ht16k33::{Display, Ht16k33State};
+    ///
+    /// let mut state = Ht16k33State::new();
+    ///
+    /// state.set_display(Display::ON);
+    ///
+    /// assert_eq!(&Display::ON, state.display());
+    /// ```
+    pub fn set_display(&mut self, display: Display) {
+        self.display = display;
+    }
+
+    /// Return the dimming state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ht16k33::{Dimming, Ht16k33State};
+    ///
+    /// let state = Ht16k33State::new();
+    ///
+    /// assert_eq!(&Dimming::BRIGHTNESS_MAX, state.dimming());
+    /// ```
+    pub fn dimming(&self) -> &Dimming {
+        &self.dimming
+    }
+
+    /// Set the dimming state.
+    ///
+    /// # Arguments
+    ///
+    /// * `dimming` - Set the dimming brightness.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ht16k33::{Dimming, Ht16k33State};
+    ///
+    /// let mut state = Ht16k33State::new();
+    ///
+    /// state.set_dimming(Dimming::BRIGHTNESS_MIN);
+    ///
+    /// assert_eq!(&Dimming::BRIGHTNESS_MIN, state.dimming());
+    /// ```
+    pub fn set_dimming(&mut self, dimming: Dimming) {
+        self.dimming = dimming;
+    }
+
+    /// Return the bytes to write the oscillator state to the system setup register.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ht16k33::{Ht16k33State, Oscillator};
+    ///
+    /// let mut state = Ht16k33State::new();
+    /// state.set_oscillator(Oscillator::ON);
+    ///
+    /// assert_eq!([0x21], state.system_setup_bytes());
+    /// ```
+    pub fn system_setup_bytes(&self) -> [u8; 1] {
+        Self::encode_system_setup(self.oscillator)
+    }
+
+    /// Return the bytes to write the display state to the display setup register.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ht16k33::{Display, Ht16k33State};
+    ///
+    /// let mut state = Ht16k33State::new();
+    /// state.set_display(Display::ON);
+    ///
+    /// assert_eq!([0x81], state.display_setup_bytes());
+    /// ```
+    pub fn display_setup_bytes(&self) -> [u8; 1] {
+        Self::encode_display_setup(self.display)
+    }
+
+    /// Return the bytes to write the dimming state to the dimming set register.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ht16k33::{Dimming, Ht16k33State};
+    ///
+    /// let mut state = Ht16k33State::new();
+    /// state.set_dimming(Dimming::BRIGHTNESS_MIN);
+    ///
+    /// assert_eq!([0xE0], state.dimming_set_bytes());
+    /// ```
+    pub fn dimming_set_bytes(&self) -> [u8; 1] {
+        Self::encode_dimming_set(self.dimming)
+    }
+
+    // Encode the system setup register command, shared with drivers that do not keep the state.
+    pub(crate) fn encode_system_setup(oscillator: Oscillator) -> [u8; 1] {
+        [(Oscillator::COMMAND | oscillator).bits()]
+    }
+
+    // Encode the display setup register command.
+    pub(crate) fn encode_display_setup(display: Display) -> [u8; 1] {
+        [(Display::COMMAND | display).bits()]
+    }
+
+    // Encode the dimming set register command.
+    pub(crate) fn encode_dimming_set(dimming: Dimming) -> [u8; 1] {
+        [(Dimming::COMMAND | dimming).bits()]
+    }
+
+    /// Return the order of the COMMON bits in each ROW of display RAM.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ht16k33::{BitOrder, Ht16k33State};
+    ///
+    /// let state = Ht16k33State::new();
+    ///
+    /// assert_eq!(BitOrder::Normal, state.bit_order());
+    /// ```
+    pub fn bit_order(&self) -> BitOrder {
+        self.bit_order
+    }
+
+    /// Set the order of the COMMON bits in each ROW of display RAM.
+    ///
+    /// # Arguments
+    ///
+    /// * `bit_order` - The order of the COMMON bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ht16k33::{BitOrder, DisplayData, Ht16k33State};
+    ///
+    /// let mut state = Ht16k33State::new();
+    /// state.set_bit_order(BitOrder::Reversed);
+    /// state.display_buffer_mut()[0] = DisplayData::COMMON_0;
+    ///
+    /// assert_eq!(DisplayData::COMMON_7, state.display_ram()[0]);
+    /// ```
+    pub fn set_bit_order(&mut self, bit_order: BitOrder) {
+        self.bit_order = bit_order;
+    }
+
+    /// Return the display RAM row that each display buffer row is written to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ht16k33::{DisplayDataAddress, Ht16k33State};
+    ///
+    /// let state = Ht16k33State::new();
+    ///
+    /// assert_eq!(DisplayDataAddress::ROW_15, state.row_map()[15]);
+    /// ```
+    pub fn row_map(&self) -> &[DisplayDataAddress; ROWS_SIZE] {
+        &self.row_map
+    }
+
+    /// Set the display RAM row that each display buffer row is written to.
+    ///
+    /// # Arguments
+    ///
+    /// * `row_map` - The display RAM row for each display buffer row, each row must be used exactly once.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ValidationError::DuplicateValue`] if a display RAM row is used more than once, the row map is
+    /// not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ht16k33::ValidationError;
+    /// use ht16k33::{DisplayData, Ht16k33State};
+    /// # fn main() -> Result<(), ValidationError> {
+    ///
+    /// let mut state = Ht16k33State::new();
+    ///
+    /// // Swap the first and last rows.
+    /// let mut row_map = *state.row_map();
+    /// row_map.swap(0, 15);
+    /// state.set_row_map(row_map)?;
+    ///
+    /// state.display_buffer_mut()[0] = DisplayData::COMMON_0;
+    /// assert_eq!(DisplayData::COMMON_0, state.display_ram()[15]);
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ValidationError::DuplicateValue`]: enum.ValidationError.html#variant.DuplicateValue
+    pub fn set_row_map(
+        &mut self,
+        row_map: [DisplayDataAddress; ROWS_SIZE],
+    ) -> Result<(), ValidationError> {
+        let mut used = [false; ROWS_SIZE];
+
+        for ram_row in row_map.iter() {
+            let index = ram_row.bits() as usize;
+
+            if used[index] {
+                return Err(ValidationError::DuplicateValue {
+                    name: "row_map",
+                    value: ram_row.bits(),
+                });
+            }
+
+            used[index] = true;
+        }
+
+        self.row_map = row_map;
+
+        Ok(())
+    }
+
+    /// Return the display buffer converted to the board wiring, in display RAM address order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ht16k33::{DisplayData, Ht16k33State};
+    ///
+    /// let mut state = Ht16k33State::new();
+    /// state.display_buffer_mut()[2] = DisplayData::COMMON_4;
+    ///
+    /// assert_eq!(DisplayData::COMMON_4, state.display_ram()[2]);
+    /// ```
+    pub fn display_ram(&self) -> [DisplayData; ROWS_SIZE] {
+        self.map_to_display_ram(&self.buffer)
+    }
+
+    /// Return the bytes to write the whole display buffer to the display RAM in a single auto-incrementing
+    /// write, starting with the first display RAM address.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ht16k33::{DisplayData, Ht16k33State};
+    ///
+    /// let mut state = Ht16k33State::new();
+    /// state.display_buffer_mut()[2] = DisplayData::COMMON_4;
+    ///
+    /// let bytes = state.display_ram_bytes();
+    ///
+    /// // The display RAM address, followed by the rows.
+    /// assert_eq!([0x00, 0x00, 0x00, 0x10], bytes[..4]);
+    /// ```
+    pub fn display_ram_bytes(&self) -> [u8; DISPLAY_RAM_BYTES + 1] {
+        let mut bytes = [0u8; DISPLAY_RAM_BYTES + 1];
+        bytes[0] = DisplayDataAddress::ROW_0.bits();
+
+        for (index, data) in self.display_ram().iter().enumerate() {
+            bytes[index + 1] = data.bits();
+        }
+
+        bytes
+    }
+
+    /// Replace the display buffer with display RAM that was read from the chip, converted from the board wiring.
+    ///
+    /// # Arguments
+    ///
+    /// * `ram` - The display RAM, in display RAM address order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ht16k33::{DisplayData, Ht16k33State, DISPLAY_RAM_BYTES};
+    ///
+    /// let mut state = Ht16k33State::new();
+    ///
+    /// let mut ram = [0u8; DISPLAY_RAM_BYTES];
+    /// ram[1] = 0b0000_0001;
+    /// state.load_display_ram(&ram);
+    ///
+    /// assert_eq!(DisplayData::COMMON_0, state.display_buffer()[1]);
+    /// ```
+    pub fn load_display_ram(&mut self, ram: &[u8; DISPLAY_RAM_BYTES]) {
+        self.buffer = self.map_from_display_ram(ram);
+    }
+
+    // Convert a frame in the logical order to the board wiring.
+    pub(crate) fn map_to_display_ram(
+        &self,
+        buffer: &[DisplayData; ROWS_SIZE],
+    ) -> [DisplayData; ROWS_SIZE] {
+        let mut ram = [DisplayData::empty(); ROWS_SIZE];
+
+        for (row, data) in buffer.iter().enumerate() {
+            ram[self.row_map[row].bits() as usize] = self.bit_order.apply(*data);
+        }
+
+        ram
+    }
+
+    // Convert display RAM in the board wiring to the logical order.
+    pub(crate) fn map_from_display_ram(
+        &self,
+        ram: &[u8; DISPLAY_RAM_BYTES],
+    ) -> [DisplayData; ROWS_SIZE] {
+        let mut buffer = [DisplayData::empty(); ROWS_SIZE];
+
+        for (row, ram_row) in self.row_map.iter().enumerate() {
+            buffer[row] = self.bit_order.apply(DisplayData::from_bits_truncate(
+                ram[ram_row.bits() as usize],
+            ));
+        }
+
+        buffer
+    }
+}
+
+impl Default for Ht16k33State {
+    fn default() -> Ht16k33State {
+        Ht16k33State::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new() {
+        let state = Ht16k33State::new();
+
+        assert_eq!(&[DisplayData::empty(); ROWS_SIZE], state.display_buffer());
+        assert_eq!(&DeviceDefaults::POWER_ON.oscillator, state.oscillator());
+        assert_eq!(&DeviceDefaults::POWER_ON.display, state.display());
+        assert_eq!(&DeviceDefaults::POWER_ON.dimming, state.dimming());
+        assert_eq!(&ROW_MAP_IDENTITY, state.row_map());
+        assert_eq!(BitOrder::Normal, state.bit_order());
+    }
+
+    #[test]
+    fn setup_bytes() {
+        let mut state = Ht16k33State::new();
+
+        state.set_oscillator(Oscillator::ON);
+        state.set_display(Display::TWO_HZ);
+        state.set_dimming(Dimming::BRIGHTNESS_MAX);
+
+        assert_eq!([0b0010_0001], state.system_setup_bytes());
+        assert_eq!([0b1000_0011], state.display_setup_bytes());
+        assert_eq!([0b1110_1111], state.dimming_set_bytes());
+    }
+
+    #[test]
+    fn display_ram_bytes() {
+        let mut state = Ht16k33State::new();
+
+        state.update_display_buffer(LedLocation::new(1, 0).unwrap(), true);
+        state.update_display_buffer(LedLocation::new(15, 7).unwrap(), true);
+
+        let mut expected = [0u8; DISPLAY_RAM_BYTES + 1];
+        expected[2] = 0b0000_0001;
+        expected[16] = 0b1000_0000;
+
+        assert_eq!(expected, state.display_ram_bytes());
+
+        state.clear_display_buffer();
+        assert_eq!([0u8; DISPLAY_RAM_BYTES + 1], state.display_ram_bytes());
+    }
+
+    #[test]
+    fn wiring() {
+        let mut state = Ht16k33State::new();
+
+        let mut row_map = ROW_MAP_IDENTITY;
+        row_map.swap(0, 15);
+        state.set_row_map(row_map).unwrap();
+        state.set_bit_order(BitOrder::Reversed);
+
+        state.update_display_buffer(LedLocation::new(0, 0).unwrap(), true);
+
+        let ram = state.display_ram();
+        assert_eq!(DisplayData::COMMON_7, ram[15]);
+        assert_eq!(DisplayData::empty(), ram[0]);
+
+        // Reading the display RAM back restores the logical order.
+        let mut bytes = [0u8; DISPLAY_RAM_BYTES];
+        for (index, data) in ram.iter().enumerate() {
+            bytes[index] = data.bits();
+        }
+
+        let mut loaded = Ht16k33State::new();
+        loaded.set_row_map(row_map).unwrap();
+        loaded.set_bit_order(BitOrder::Reversed);
+        loaded.load_display_ram(&bytes);
+
+        assert_eq!(state.display_buffer(), loaded.display_buffer());
+    }
+
+    #[test]
+    fn set_row_map_duplicate() {
+        let mut state = Ht16k33State::new();
+
+        let mut row_map = ROW_MAP_IDENTITY;
+        row_map[3] = DisplayDataAddress::ROW_4;
+
+        assert!(matches!(
+            state.set_row_map(row_map),
+            Err(ValidationError::DuplicateValue {
+                name: "row_map",
+                value: 4
+            })
+        ));
+        assert_eq!(&ROW_MAP_IDENTITY, state.row_map());
+    }
+}
@@ -0,0 +1,202 @@
+//! Worst-case I2C transaction times, for budgeting control loops.
+//!
+//! The times are computed from the number of bits clocked on the bus: 9 bits (8 data bits and the ACK) per
+//! byte, including the device address byte, plus a START and STOP condition per transaction and a repeated
+//! START for reads. Clock stretching and the bus free time between transactions are not included.
+//!
+//! All times are in microseconds, rounded up.
+//!
+//! # Examples
+//!
+//! ```
+//! use ht16k33::timing;
+//!
+//! // Writing the whole display buffer at 100 kHz takes under 2 ms.
+//! assert!(timing::full_flush_us(timing::STANDARD_MODE_HZ) < 2_000);
+//!
+//! // So does writing it 4 times at 400 kHz.
+//! assert!(4 * timing::full_flush_us(timing::FAST_MODE_HZ) < 2_000);
+//! ```
+use crate::constants::{KEY_DATA_SIZE, ROWS_SIZE};
+
+/// The I2C Standard-mode bus speed, 100 kHz.
+pub const STANDARD_MODE_HZ: u32 = 100_000;
+
+/// The I2C Fast-mode bus speed, 400 kHz, which is the maximum supported by the HT16K33.
+pub const FAST_MODE_HZ: u32 = 400_000;
+
+// Bits clocked per byte, including the ACK.
+const BITS_PER_BYTE: u64 = 9;
+
+// Bits clocked for a START or STOP condition.
+const CONDITION_BITS: u64 = 1;
+
+// Convert a number of bits to microseconds at the given bus speed, rounding up.
+const fn bits_to_us(bits: u64, bus_hz: u32) -> u32 {
+    if bus_hz == 0 {
+        return u32::MAX;
+    }
+
+    // Round up without `bits * 1_000_000 + bus_hz - 1`, which overflows when the product saturates.
+    let scaled = bits.saturating_mul(1_000_000);
+    let us = scaled / bus_hz as u64 + (scaled % bus_hz as u64 != 0) as u64;
+
+    if us > u32::MAX as u64 {
+        u32::MAX
+    } else {
+        us as u32
+    }
+}
+
+/// Return the time to write `len` bytes, e.g. a command or a register address and its data.
+///
+/// A `bus_hz` of `0` returns `u32::MAX`.
+///
+/// # Arguments
+///
+/// * `bus_hz` - The I2C bus speed, in Hz.
+/// * `len` - The number of bytes written, not including the device address.
+///
+/// # Examples
+///
+/// ```
+/// use ht16k33::timing;
+///
+/// // Address and command bytes, plus START and STOP.
+/// assert_eq!(200, timing::write_us(timing::STANDARD_MODE_HZ, 1));
+/// ```
+pub const fn write_us(bus_hz: u32, len: usize) -> u32 {
+    let bytes = (len as u64).saturating_add(1);
+    let bits = BITS_PER_BYTE
+        .saturating_mul(bytes)
+        .saturating_add(2 * CONDITION_BITS);
+
+    bits_to_us(bits, bus_hz)
+}
+
+/// Return the time to write `write_len` bytes, and then read `read_len` bytes after a repeated START.
+///
+/// A `bus_hz` of `0` returns `u32::MAX`.
+///
+/// # Arguments
+///
+/// * `bus_hz` - The I2C bus speed, in Hz.
+/// * `write_len` - The number of bytes written, not including the device address.
+/// * `read_len` - The number of bytes read.
+///
+/// # Examples
+///
+/// ```
+/// use ht16k33::timing;
+///
+/// // Reading the INT flag.
+/// let us = timing::write_read_us(timing::FAST_MODE_HZ, 1, 1);
+/// ```
+pub const fn write_read_us(bus_hz: u32, write_len: usize, read_len: usize) -> u32 {
+    let bytes = (write_len as u64)
+        .saturating_add(read_len as u64)
+        .saturating_add(2);
+    let bits = BITS_PER_BYTE
+        .saturating_mul(bytes)
+        .saturating_add(3 * CONDITION_BITS);
+
+    bits_to_us(bits, bus_hz)
+}
+
+/// Return the time to write the whole display buffer in one transaction, see `write_display_buffer()`.
+///
+/// # Arguments
+///
+/// * `bus_hz` - The I2C bus speed, in Hz.
+///
+/// # Examples
+///
+/// ```
+/// use ht16k33::timing;
+///
+/// assert_eq!(1_640, timing::full_flush_us(timing::STANDARD_MODE_HZ));
+/// ```
+pub const fn full_flush_us(bus_hz: u32) -> u32 {
+    write_us(bus_hz, 1 + ROWS_SIZE)
+}
+
+/// Return the time to write a single row of commons, see `set_led()`.
+///
+/// # Arguments
+///
+/// * `bus_hz` - The I2C bus speed, in Hz.
+///
+/// # Examples
+///
+/// ```
+/// use ht16k33::timing;
+///
+/// assert_eq!(290, timing::single_common_us(timing::STANDARD_MODE_HZ));
+/// ```
+pub const fn single_common_us(bus_hz: u32) -> u32 {
+    write_us(bus_hz, 2)
+}
+
+/// Return the time to read the whole key data RAM, see `read_key_buffer()`.
+///
+/// # Arguments
+///
+/// * `bus_hz` - The I2C bus speed, in Hz.
+///
+/// # Examples
+///
+/// ```
+/// use ht16k33::timing;
+///
+/// assert_eq!(840, timing::key_read_us(timing::STANDARD_MODE_HZ));
+/// ```
+pub const fn key_read_us(bus_hz: u32) -> u32 {
+    write_read_us(bus_hz, 1, KEY_DATA_SIZE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_us() {
+        assert_eq!(200, super::write_us(STANDARD_MODE_HZ, 1));
+        assert_eq!(50, super::write_us(FAST_MODE_HZ, 1));
+        assert_eq!(20, super::write_us(1_000_000, 1));
+    }
+
+    #[test]
+    fn write_read_us() {
+        assert_eq!(390, super::write_read_us(STANDARD_MODE_HZ, 1, 1));
+        assert_eq!(98, super::write_read_us(FAST_MODE_HZ, 1, 1));
+    }
+
+    #[test]
+    fn rounds_up() {
+        // 20 bits at 3 Hz is 6.67 s.
+        assert_eq!(6_666_667, super::write_us(3, 1));
+    }
+
+    #[test]
+    fn no_panic() {
+        assert_eq!(u32::MAX, super::write_us(0, 1));
+        assert_eq!(u32::MAX, super::write_read_us(0, 1, 1));
+        assert_eq!(u32::MAX, super::write_us(1, usize::MAX / 16));
+
+        for &bus_hz in &[1, STANDARD_MODE_HZ, FAST_MODE_HZ, u32::MAX] {
+            assert_eq!(u32::MAX, super::write_us(bus_hz, usize::MAX));
+            assert_eq!(u32::MAX, super::write_read_us(bus_hz, usize::MAX, 0));
+            assert_eq!(u32::MAX, super::write_read_us(bus_hz, 0, usize::MAX));
+            assert_eq!(
+                u32::MAX,
+                super::write_read_us(bus_hz, usize::MAX, usize::MAX)
+            );
+        }
+    }
+
+    #[test]
+    fn budgets() {
+        assert!(single_common_us(FAST_MODE_HZ) < key_read_us(FAST_MODE_HZ));
+        assert!(key_read_us(FAST_MODE_HZ) < full_flush_us(FAST_MODE_HZ));
+    }
+}
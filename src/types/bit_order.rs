@@ -0,0 +1,85 @@
+use crate::types::DisplayData;
+
+use core::fmt;
+
+/// The order of the COMMON bits in each ROW of display RAM.
+///
+/// Some custom boards wire the COMMONs in reverse, so the driver can reverse the bits when writing to the chip.
+///
+/// # Example
+///
+/// ```
+/// use ht16k33::{BitOrder, DisplayData};
+///
+/// assert_eq!(DisplayData::COMMON_7, BitOrder::Reversed.apply(DisplayData::COMMON_0));
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum BitOrder {
+    /// COMMON 0 is the least significant bit.
+    ///
+    /// *This is the default.*
+    Normal,
+    /// COMMON 0 is the most significant bit.
+    Reversed,
+}
+
+impl Default for BitOrder {
+    fn default() -> BitOrder {
+        BitOrder::Normal
+    }
+}
+
+impl BitOrder {
+    /// Convert the `data` between the display buffer and display RAM bit orders.
+    ///
+    /// Converting twice returns the original `data`.
+    pub const fn apply(self, data: DisplayData) -> DisplayData {
+        match self {
+            BitOrder::Normal => data,
+            BitOrder::Reversed => DisplayData::from_bits_truncate(data.bits().reverse_bits()),
+        }
+    }
+}
+
+impl fmt::Display for BitOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BitOrder::Normal => write!(f, "BitOrder::Normal"),
+            BitOrder::Reversed => write!(f, "BitOrder::Reversed"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default() {
+        assert_eq!(
+            BitOrder::Normal,
+            BitOrder::default(),
+            "BitOrder default is Normal"
+        );
+    }
+
+    #[test]
+    fn apply() {
+        let data = DisplayData::COMMON_0 | DisplayData::COMMON_2;
+
+        assert_eq!(data, BitOrder::Normal.apply(data));
+        assert_eq!(
+            DisplayData::COMMON_7 | DisplayData::COMMON_5,
+            BitOrder::Reversed.apply(data)
+        );
+
+        for value in 0..=255u8 {
+            let data = DisplayData::from_bits_truncate(value);
+
+            assert_eq!(
+                data,
+                BitOrder::Reversed.apply(BitOrder::Reversed.apply(data))
+            );
+        }
+    }
+}
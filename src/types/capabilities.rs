@@ -0,0 +1,77 @@
+use crate::constants::{COMMONS_SIZE, KEY_DATA_SIZE, KEY_LINES_SIZE, KEY_SCANS_SIZE, ROWS_SIZE};
+
+use core::fmt;
+
+/// What the supported HT16K33 package provides, so generic code can adapt to it.
+///
+/// Only the 28-pin SOP package is currently supported, see [`Capabilities::SOP28`].
+///
+/// # Example
+///
+/// ```
+/// use ht16k33::Capabilities;
+///
+/// let capabilities = Capabilities::SOP28;
+///
+/// assert_eq!(128, capabilities.leds());
+/// ```
+///
+/// [`Capabilities::SOP28`]: struct.Capabilities.html#associatedconstant.SOP28
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Capabilities {
+    /// The number of ROW outputs, i.e. LEDs per COMMON.
+    pub rows: u8,
+    /// The number of COMMON outputs, i.e. LEDs per ROW.
+    pub commons: u8,
+    /// The number of keys that can be scanned.
+    pub keys: u8,
+    /// Whether the package has an INT pin, it is shared with the last ROW output.
+    pub int_pin: bool,
+}
+
+impl Capabilities {
+    /// The 28-pin SOP package: 16 ROWs, 8 COMMONs, 39 keys (13 K lines by 3 KS lines), and a ROW/INT pin.
+    pub const SOP28: Capabilities = Capabilities {
+        rows: ROWS_SIZE as u8,
+        commons: COMMONS_SIZE as u8,
+        keys: (KEY_LINES_SIZE * KEY_SCANS_SIZE) as u8,
+        int_pin: true,
+    };
+
+    /// Return the number of LEDs that can be driven.
+    pub const fn leds(&self) -> u16 {
+        self.rows as u16 * self.commons as u16
+    }
+
+    /// Return the number of bytes of key data RAM.
+    pub const fn key_data_bytes(&self) -> usize {
+        KEY_DATA_SIZE
+    }
+}
+
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Capabilities(rows: {}, commons: {}, keys: {}, int_pin: {})",
+            self.rows, self.commons, self.keys, self.int_pin
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sop28() {
+        let capabilities = Capabilities::SOP28;
+
+        assert_eq!(ROWS_SIZE, capabilities.rows as usize);
+        assert_eq!(COMMONS_SIZE, capabilities.commons as usize);
+        assert_eq!(128, capabilities.leds());
+
+        // Each key uses one bit of key data RAM.
+        assert!(capabilities.keys as usize <= capabilities.key_data_bytes() * 8);
+    }
+}
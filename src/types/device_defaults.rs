@@ -0,0 +1,73 @@
+use crate::types::{Dimming, Display, Oscillator};
+
+use core::fmt;
+
+/// The HT16K33 Power-on Reset state of the write-only setup registers.
+///
+/// The display RAM is undefined after a Power-on Reset, so it is not part of these defaults.
+///
+/// # Example
+///
+/// ```
+/// use ht16k33::DeviceDefaults;
+/// use ht16k33::Oscillator;
+///
+/// assert_eq!(Oscillator::OFF, DeviceDefaults::POWER_ON.oscillator);
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct DeviceDefaults {
+    /// The oscillator state.
+    pub oscillator: Oscillator,
+    /// The display state.
+    pub display: Display,
+    /// The dimming state.
+    pub dimming: Dimming,
+}
+
+impl DeviceDefaults {
+    /// The Power-on Reset defaults: oscillator off, display off, and maximum brightness.
+    pub const POWER_ON: DeviceDefaults = DeviceDefaults {
+        oscillator: Oscillator::OFF,
+        display: Display::OFF,
+        dimming: Dimming::BRIGHTNESS_MAX,
+    };
+}
+
+impl Default for DeviceDefaults {
+    fn default() -> DeviceDefaults {
+        DeviceDefaults::POWER_ON
+    }
+}
+
+impl fmt::Display for DeviceDefaults {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "DeviceDefaults(oscillator: {}, display: {}, dimming: {})",
+            self.oscillator, self.display, self.dimming
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default() {
+        assert_eq!(
+            DeviceDefaults::POWER_ON,
+            DeviceDefaults::default(),
+            "DeviceDefaults default is POWER_ON"
+        );
+    }
+
+    #[test]
+    fn power_on() {
+        let defaults = DeviceDefaults::POWER_ON;
+
+        assert_eq!(Oscillator::default(), defaults.oscillator);
+        assert_eq!(Display::default(), defaults.display);
+        assert_eq!(Dimming::default(), defaults.dimming);
+    }
+}
@@ -133,6 +133,29 @@ impl Dimming {
 
         Ok(Dimming::from_bits_truncate(value))
     }
+
+    /// Return a `Dimming` value from the given `u8`, without validating it.
+    ///
+    /// For hot paths where the value is already known to be in range. The value is only checked by
+    /// `debug_assert!`, so an out-of-range value panics in debug builds and is truncated in release builds.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ht16k33::Dimming;
+    ///
+    /// let brightness = Dimming::from_u8_unchecked(1u8);
+    ///
+    /// assert_eq!(1u8, brightness.bits());
+    /// ```
+    pub fn from_u8_unchecked(value: u8) -> Self {
+        debug_assert!(
+            value <= Dimming::BRIGHTNESS_MAX.bits(),
+            "'dimming' value must be less than or equal to BRIGHTNESS_MAX"
+        );
+
+        Dimming::from_bits_truncate(value)
+    }
 }
 
 #[cfg(test)]
@@ -179,4 +202,44 @@ mod tests {
     fn from_u8_too_large() {
         let _ = Dimming::from_u8(16u8).unwrap();
     }
+
+    #[test]
+    fn from_u8_unchecked() {
+        for value in 0u8..16 {
+            let dimming = Dimming::from_u8_unchecked(value);
+            assert_eq!(value, dimming.bits());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(debug_assertions)]
+    fn from_u8_unchecked_too_large() {
+        let _ = Dimming::from_u8_unchecked(16u8);
+    }
+
+    #[test]
+    fn from_u8_exhaustive() {
+        extern crate std;
+        use std::string::ToString;
+
+        for value in 0..=255u8 {
+            match Dimming::from_u8(value) {
+                Ok(dimming) => {
+                    assert!(value <= Dimming::BRIGHTNESS_MAX.bits());
+                    assert_eq!(value, dimming.bits());
+                    assert_eq!(dimming, Dimming::from_u8_unchecked(value));
+                }
+                Err(ValidationError::ValueTooLarge { value: error, .. }) => {
+                    assert!(value > Dimming::BRIGHTNESS_MAX.bits());
+                    assert_eq!(value, error);
+                }
+                Err(error) => panic!("unexpected error: {:?}", error),
+            }
+
+            let dimming = Dimming::from_bits_truncate(value);
+            assert_eq!(value & 0b1110_1111, dimming.bits());
+            assert!(dimming.to_string().starts_with("Dimming::"));
+        }
+    }
 }
@@ -61,4 +61,16 @@ mod tests {
         assert!(Display::ONE_HZ.contains(Display::ON), "ONE_HZ includes ON");
         assert!(Display::TWO_HZ.contains(Display::ON), "TWO_HZ includes ON");
     }
+
+    #[test]
+    fn from_bits_exhaustive() {
+        extern crate std;
+        use std::string::ToString;
+
+        for value in 0..=255u8 {
+            let display = Display::from_bits_truncate(value);
+            assert_eq!(value & 0b1000_0111, display.bits());
+            assert!(display.to_string().starts_with("Display::"));
+        }
+    }
 }
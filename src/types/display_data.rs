@@ -1,10 +1,16 @@
+use crate::constants::COMMONS_SIZE;
+use crate::errors::ValidationError;
 use bitflags::bitflags;
 use core::fmt;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 bitflags! {
     /// RAM data for LED display.
     ///
     /// The LED for the corresponding bitflag will be enabled if the flag is `1`.
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub struct DisplayData: u8 {
         /// No LEDs enabled.
         const COMMON_NONE = 0b0000_0000;
@@ -50,6 +56,128 @@ impl fmt::Display for DisplayData {
     }
 }
 
+impl DisplayData {
+    /// Return a validated `DisplayData` value with only the LED on the given `common` enabled.
+    ///
+    /// # Errors
+    ///
+    /// The `common` value is validated to be within the [`COMMONS_SIZE`] range of the device. If validation
+    /// fails then [`ht16k33::ValidationError::ValueTooLarge`] is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ht16k33::DisplayData;
+    /// # use ht16k33::ValidationError;
+    /// # fn main() -> Result<(), ValidationError> {
+    ///
+    /// let data = DisplayData::from_common(2u8)?;
+    ///
+    /// assert_eq!(DisplayData::COMMON_2, data);
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`COMMONS_SIZE`]: constant.COMMONS_SIZE.html
+    /// [`ht16k33::ValidationError::ValueTooLarge`]: enum.ValidationError.html#variant.ValueTooLarge
+    pub fn from_common(common: u8) -> Result<Self, ValidationError> {
+        if common >= COMMONS_SIZE as u8 {
+            return Err(ValidationError::ValueTooLarge {
+                name: "common",
+                value: common,
+                limit: COMMONS_SIZE as u8,
+                inclusive: false,
+            });
+        }
+
+        Ok(DisplayData::from_bits_truncate(1 << common))
+    }
+
+    /// Return the `common` index of a single enabled LED.
+    ///
+    /// Returns `None` if no LEDs, or more than one LED, are enabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ht16k33::DisplayData;
+    ///
+    /// assert_eq!(Some(2u8), DisplayData::COMMON_2.common_index());
+    /// assert_eq!(None, DisplayData::COMMON_NONE.common_index());
+    /// assert_eq!(None, (DisplayData::COMMON_2 | DisplayData::COMMON_3).common_index());
+    /// ```
+    pub fn common_index(self) -> Option<u8> {
+        if self.bits().count_ones() != 1 {
+            return None;
+        }
+
+        Some(self.bits().trailing_zeros() as u8)
+    }
+
+    /// Return an iterator over the `common` indexes of the enabled LEDs, in ascending order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ht16k33::DisplayData;
+    ///
+    /// let data = DisplayData::COMMON_1 | DisplayData::COMMON_6;
+    /// let mut commons = data.iter_ones();
+    ///
+    /// assert_eq!(Some(1u8), commons.next());
+    /// assert_eq!(Some(6u8), commons.next());
+    /// assert_eq!(None, commons.next());
+    /// ```
+    pub fn iter_ones(self) -> CommonIndexes {
+        CommonIndexes { bits: self.bits() }
+    }
+}
+
+impl IntoIterator for DisplayData {
+    type Item = u8;
+    type IntoIter = CommonIndexes;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_ones()
+    }
+}
+
+/// Iterator over the `common` indexes of the enabled LEDs in a [`DisplayData`].
+///
+/// Created by [`DisplayData::iter_ones`].
+///
+/// [`DisplayData`]: struct.DisplayData.html
+/// [`DisplayData::iter_ones`]: struct.DisplayData.html#method.iter_ones
+#[derive(Clone, Debug)]
+pub struct CommonIndexes {
+    bits: u8,
+}
+
+impl Iterator for CommonIndexes {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.bits == 0 {
+            return None;
+        }
+
+        let common = self.bits.trailing_zeros() as u8;
+
+        // Clear the lowest enabled bit.
+        self.bits &= self.bits - 1;
+
+        Some(common)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.bits.count_ones() as usize;
+        (count, Some(count))
+    }
+}
+
+impl ExactSizeIterator for CommonIndexes {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -76,4 +204,83 @@ mod tests {
 
         assert_eq!(data, DisplayData::all(), "DisplayData is all enabled");
     }
+
+    #[test]
+    fn from_common() {
+        for common in 0u8..8 {
+            let data = DisplayData::from_common(common).unwrap();
+            assert_eq!(1u8 << common, data.bits());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_common_too_large() {
+        let _ = DisplayData::from_common(8u8).unwrap();
+    }
+
+    #[test]
+    fn common_index() {
+        for common in 0u8..8 {
+            let data = DisplayData::from_common(common).unwrap();
+            assert_eq!(Some(common), data.common_index());
+        }
+
+        assert_eq!(None, DisplayData::COMMON_NONE.common_index());
+        assert_eq!(None, DisplayData::all().common_index());
+    }
+
+    #[test]
+    fn iter_ones() {
+        let data = DisplayData::COMMON_0 | DisplayData::COMMON_3 | DisplayData::COMMON_7;
+
+        let mut commons = data.iter_ones();
+        assert_eq!(3, commons.len());
+        assert_eq!(Some(0), commons.next());
+        assert_eq!(Some(3), commons.next());
+        assert_eq!(Some(7), commons.next());
+        assert_eq!(None, commons.next());
+
+        assert_eq!(0, DisplayData::COMMON_NONE.iter_ones().count());
+        assert_eq!(8, DisplayData::all().iter_ones().count());
+    }
+
+    #[test]
+    fn into_iter() {
+        let data = DisplayData::COMMON_2 | DisplayData::COMMON_5;
+
+        let mut commons = [0u8; 2];
+        for (index, common) in data.into_iter().enumerate() {
+            commons[index] = common;
+        }
+
+        assert_eq!([2, 5], commons);
+    }
+
+    #[test]
+    fn from_bits_exhaustive() {
+        extern crate std;
+        use std::string::ToString;
+
+        for value in 0..=255u8 {
+            let data = DisplayData::from_bits_truncate(value);
+            assert_eq!(value, data.bits());
+            assert!(data.to_string().starts_with("DisplayData::"));
+
+            // Rebuilding the value from its common indexes is lossless.
+            let commons = data
+                .iter_ones()
+                .fold(0u8, |bits, common| bits | (1 << common));
+            assert_eq!(value, commons);
+            assert_eq!(value.count_ones() as usize, data.iter_ones().count());
+
+            match DisplayData::from_common(value) {
+                Ok(data) => {
+                    assert!((value as usize) < COMMONS_SIZE);
+                    assert_eq!(Some(value), data.common_index());
+                }
+                Err(_) => assert!((value as usize) >= COMMONS_SIZE),
+            }
+        }
+    }
 }
@@ -1,8 +1,12 @@
 use bitflags::bitflags;
 use core::fmt;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 bitflags! {
     /// Display RAM data address.
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub struct DisplayDataAddress: u8 {
         /// Row 0
         const ROW_0 = 0;
@@ -81,4 +85,19 @@ mod tests {
             "DisplayDataAddress default is row 0"
         );
     }
+
+    #[test]
+    fn from_bits_exhaustive() {
+        extern crate std;
+        use std::string::ToString;
+
+        for value in 0..=255u8 {
+            let address = DisplayDataAddress::from_bits_truncate(value);
+            assert_eq!(value & 0b0000_1111, address.bits());
+            assert_eq!(
+                std::format!("DisplayDataAddress::ROW_{}", address.bits()),
+                address.to_string()
+            );
+        }
+    }
 }
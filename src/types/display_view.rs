@@ -0,0 +1,142 @@
+use crate::constants::ROWS_SIZE;
+use crate::types::{Dimming, Display, DisplayData, DisplayDataAddress, LedLocation, Oscillator};
+
+/// A read-only view of the HT16K33 display state.
+///
+/// Created by [`HT16K33::view`], it borrows the display buffer immutably so the display state can be inspected
+/// without borrowing the driver mutably.
+///
+/// # Example
+///
+/// ```
+/// use ht16k33::i2c_mock::I2cMock;
+/// use ht16k33::HT16K33;
+/// use ht16k33::LedLocation;
+/// # use ht16k33::ValidationError;
+/// # fn main() -> Result<(), ValidationError> {
+///
+/// let mut ht16k33 = HT16K33::new(I2cMock::new(), 0u8);
+///
+/// let location = LedLocation::new(1, 2)?;
+/// ht16k33.update_display_buffer(location, true);
+///
+/// let view = ht16k33.view();
+///
+/// assert!(view.is_led_on(location));
+///
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`HT16K33::view`]: struct.HT16K33.html#method.view
+#[derive(Clone, Copy, Debug)]
+pub struct DisplayView<'a> {
+    buffer: &'a [DisplayData; ROWS_SIZE],
+    oscillator: Oscillator,
+    display: Display,
+    dimming: Dimming,
+}
+
+impl<'a> DisplayView<'a> {
+    pub(crate) fn new(
+        buffer: &'a [DisplayData; ROWS_SIZE],
+        oscillator: Oscillator,
+        display: Display,
+        dimming: Dimming,
+    ) -> Self {
+        DisplayView {
+            buffer,
+            oscillator,
+            display,
+            dimming,
+        }
+    }
+
+    /// Return the display buffer.
+    pub fn display_buffer(&self) -> &'a [DisplayData; ROWS_SIZE] {
+        self.buffer
+    }
+
+    /// Return the common data of the given `row` in the display buffer.
+    pub fn row(&self, row: DisplayDataAddress) -> DisplayData {
+        self.buffer[row.bits() as usize]
+    }
+
+    /// Return whether the LED at the given `location` is enabled in the display buffer.
+    pub fn is_led_on(&self, location: LedLocation) -> bool {
+        self.buffer[location.row_as_index()].contains(location.common)
+    }
+
+    /// Return the oscillator state.
+    pub fn oscillator(&self) -> Oscillator {
+        self.oscillator
+    }
+
+    /// Return the display state.
+    pub fn display(&self) -> Display {
+        self.display
+    }
+
+    /// Return the dimming state.
+    pub fn dimming(&self) -> Dimming {
+        self.dimming
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_led_on() {
+        let mut buffer = [DisplayData::COMMON_NONE; ROWS_SIZE];
+        buffer[1] = DisplayData::COMMON_2;
+
+        let view = DisplayView::new(
+            &buffer,
+            Oscillator::ON,
+            Display::ON,
+            Dimming::BRIGHTNESS_MAX,
+        );
+
+        assert!(view.is_led_on(LedLocation::new(1, 2).unwrap()));
+        assert!(!view.is_led_on(LedLocation::new(1, 3).unwrap()));
+        assert!(!view.is_led_on(LedLocation::new(2, 2).unwrap()));
+    }
+
+    #[test]
+    fn row() {
+        let mut buffer = [DisplayData::COMMON_NONE; ROWS_SIZE];
+        buffer[15] = DisplayData::all();
+
+        let view = DisplayView::new(
+            &buffer,
+            Oscillator::ON,
+            Display::ON,
+            Dimming::BRIGHTNESS_MAX,
+        );
+
+        assert_eq!(DisplayData::all(), view.row(DisplayDataAddress::ROW_15));
+        assert_eq!(
+            DisplayData::COMMON_NONE,
+            view.row(DisplayDataAddress::ROW_0)
+        );
+    }
+
+    #[test]
+    fn state() {
+        let buffer = [DisplayData::COMMON_NONE; ROWS_SIZE];
+
+        let view = DisplayView::new(
+            &buffer,
+            Oscillator::ON,
+            Display::HALF_HZ,
+            Dimming::BRIGHTNESS_MIN,
+        );
+
+        assert_eq!(Oscillator::ON, view.oscillator());
+        assert_eq!(Display::HALF_HZ, view.display());
+        assert_eq!(Dimming::BRIGHTNESS_MIN, view.dimming());
+        assert_eq!(&buffer, view.display_buffer());
+    }
+}
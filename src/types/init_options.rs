@@ -0,0 +1,77 @@
+use crate::types::{DeviceDefaults, Dimming, Display};
+
+use core::fmt;
+
+/// Duration of a brightness ramp, in milliseconds.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct RampMs(pub u16);
+
+/// Options for initializing the HT16K33.
+///
+/// The default options match the Power-on Reset defaults, without a brightness ramp.
+///
+/// # Example
+///
+/// ```
+/// use ht16k33::{Dimming, Display, InitOptions, RampMs};
+///
+/// // Fade in from dark to half brightness over 1 second.
+/// let options = InitOptions {
+///     brightness: Dimming::BRIGHTNESS_8_16,
+///     display: Display::ON,
+///     ramp: Some(RampMs(1000)),
+/// };
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct InitOptions {
+    /// The display brightness.
+    pub brightness: Dimming,
+    /// The display state.
+    pub display: Display,
+    /// Ramp the brightness up from the minimum over this duration, instead of setting it immediately.
+    pub ramp: Option<RampMs>,
+}
+
+impl Default for InitOptions {
+    fn default() -> InitOptions {
+        InitOptions {
+            brightness: DeviceDefaults::POWER_ON.dimming,
+            display: DeviceDefaults::POWER_ON.display,
+            ramp: None,
+        }
+    }
+}
+
+impl fmt::Display for InitOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.ramp {
+            Some(RampMs(ramp)) => write!(
+                f,
+                "InitOptions(brightness: {}, display: {}, ramp: {}ms)",
+                self.brightness, self.display, ramp
+            ),
+            None => write!(
+                f,
+                "InitOptions(brightness: {}, display: {})",
+                self.brightness, self.display
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default() {
+        let options = InitOptions::default();
+
+        assert!(
+            Dimming::BRIGHTNESS_MAX == options.brightness
+                && Display::OFF == options.display
+                && options.ramp.is_none(),
+            "InitOptions default is the Power-on Reset defaults without a ramp"
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use crate::constants::{KEY_DATA_SIZE, KEY_LINES_SIZE, KEY_SCANS_SIZE};
+use crate::types::KeyLocation;
+
+use core::fmt;
+
+/// The key data RAM, one bit per key of the 13x3 keyscan matrix.
+///
+/// Each KS line uses two bytes: keys `K1` to `K8` are bits 0-7 of the first byte, and keys `K9` to `K13` are
+/// bits 0-4 of the second byte. A set bit means the key is pressed.
+///
+/// # Example
+///
+/// ```
+/// use ht16k33::KeyData;
+///
+/// let key_data = KeyData::new([0b0000_0001, 0, 0, 0, 0, 0]);
+///
+/// assert!(key_data.any_pressed());
+///
+/// for location in key_data.pressed() {
+///     assert_eq!((0, 0), (location.ks(), location.k()));
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct KeyData([u8; KEY_DATA_SIZE]);
+
+impl KeyData {
+    /// Create a `KeyData` from the key data RAM bytes, the unused bits are cleared.
+    pub const fn new(bytes: [u8; KEY_DATA_SIZE]) -> Self {
+        let mut bytes = bytes;
+
+        let mut index = 1;
+        while index < KEY_DATA_SIZE {
+            bytes[index] &= 0b0001_1111;
+            index += 2;
+        }
+
+        KeyData(bytes)
+    }
+
+    /// Return the key data RAM bytes.
+    pub const fn bytes(&self) -> &[u8; KEY_DATA_SIZE] {
+        &self.0
+    }
+
+    /// Return whether any key is pressed.
+    pub const fn any_pressed(&self) -> bool {
+        let mut index = 0;
+        while index < KEY_DATA_SIZE {
+            if self.0[index] != 0 {
+                return true;
+            }
+            index += 1;
+        }
+
+        false
+    }
+
+    /// Return whether the key at the given location is pressed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ht16k33::{KeyData, KeyLocation};
+    /// # use ht16k33::ValidationError;
+    /// # fn main() -> Result<(), ValidationError> {
+    ///
+    /// let key_data = KeyData::new([0, 0, 0, 0b0001_0000, 0, 0]);
+    ///
+    /// assert!(key_data.is_pressed(KeyLocation::new(1, 12)?));
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn is_pressed(&self, location: KeyLocation) -> bool {
+        let (index, mask) = location.key_data_bit();
+
+        self.0[index] & mask != 0
+    }
+
+    /// Return an iterator over the locations of the pressed keys, ordered by `ks` and then `k`.
+    pub fn pressed(&self) -> PressedKeys {
+        PressedKeys {
+            key_data: *self,
+            index: 0,
+        }
+    }
+}
+
+impl IntoIterator for KeyData {
+    type Item = KeyLocation;
+    type IntoIter = PressedKeys;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.pressed()
+    }
+}
+
+/// Iterator over the locations of the pressed keys in a [`KeyData`].
+///
+/// Created by [`KeyData::pressed`].
+///
+/// [`KeyData`]: struct.KeyData.html
+/// [`KeyData::pressed`]: struct.KeyData.html#method.pressed
+#[derive(Clone, Debug)]
+pub struct PressedKeys {
+    key_data: KeyData,
+    // The next key to check, in `ks * KEY_LINES_SIZE + k` order.
+    index: usize,
+}
+
+impl Iterator for PressedKeys {
+    type Item = KeyLocation;
+
+    fn next(&mut self) -> Option<KeyLocation> {
+        while self.index < KEY_SCANS_SIZE * KEY_LINES_SIZE {
+            let ks = (self.index / KEY_LINES_SIZE) as u8;
+            let k = (self.index % KEY_LINES_SIZE) as u8;
+
+            self.index += 1;
+
+            let location = KeyLocation::new_unchecked(ks, k);
+            if self.key_data.is_pressed(location) {
+                return Some(location);
+            }
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.clone().fold(0, |count, _| count + 1);
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for PressedKeys {}
+
+impl From<[u8; KEY_DATA_SIZE]> for KeyData {
+    fn from(bytes: [u8; KEY_DATA_SIZE]) -> KeyData {
+        KeyData::new(bytes)
+    }
+}
+
+impl fmt::Display for KeyData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "KeyData(")?;
+
+        for (index, byte) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{:#010b}", byte)?;
+        }
+
+        write!(f, ")")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default() {
+        assert_eq!(
+            KeyData::new([0; KEY_DATA_SIZE]),
+            KeyData::default(),
+            "KeyData default is no keys pressed"
+        );
+    }
+
+    #[test]
+    fn new() {
+        let key_data = KeyData::new([0xFF; KEY_DATA_SIZE]);
+
+        assert_eq!(
+            &[0xFF, 0x1F, 0xFF, 0x1F, 0xFF, 0x1F],
+            key_data.bytes(),
+            "Unused bits are cleared"
+        );
+    }
+
+    #[test]
+    fn any_pressed() {
+        assert!(!KeyData::default().any_pressed());
+        assert!(KeyData::new([0, 0, 0, 0, 0, 0b0001_0000]).any_pressed());
+
+        // Unused bits are not keys.
+        assert!(!KeyData::new([0, 0b1110_0000, 0, 0, 0, 0]).any_pressed());
+    }
+
+    #[test]
+    fn is_pressed() {
+        let key_data = KeyData::new([0b1000_0001, 0, 0, 0, 0, 0b0001_0000]);
+
+        assert!(key_data.is_pressed(KeyLocation::new(0, 0).unwrap()));
+        assert!(key_data.is_pressed(KeyLocation::new(0, 7).unwrap()));
+        assert!(key_data.is_pressed(KeyLocation::new(2, 12).unwrap()));
+        assert!(!key_data.is_pressed(KeyLocation::new(1, 0).unwrap()));
+    }
+
+    #[test]
+    fn pressed() {
+        let key_data = KeyData::new([0b0000_0010, 0, 0, 0b0000_0001, 0b1111_1111, 0b0001_1111]);
+
+        let mut pressed = key_data.pressed();
+        assert_eq!(15, pressed.len());
+
+        assert_eq!(Some(KeyLocation::new(0, 1).unwrap()), pressed.next());
+        assert_eq!(Some(KeyLocation::new(1, 8).unwrap()), pressed.next());
+        assert_eq!(13, pressed.len());
+
+        for k in 0..KEY_LINES_SIZE as u8 {
+            assert_eq!(Some(KeyLocation::new(2, k).unwrap()), pressed.next());
+        }
+
+        assert_eq!(None, pressed.next());
+        assert_eq!(0, KeyData::default().into_iter().count());
+    }
+
+    #[test]
+    fn display() {
+        extern crate std;
+        use std::string::ToString;
+
+        assert_eq!(
+            "KeyData(0b00000001, 0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00010000)",
+            KeyData::new([1, 0, 0, 0, 0, 0b0001_0000]).to_string()
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use crate::constants::{KEY_LINES_SIZE, KEY_SCANS_SIZE};
+use crate::errors::ValidationError;
+
+use core::fmt;
+
+/// Represents a key location in the keyscan matrix.
+///
+/// The key location is a (`ks`, `k`) pair of the key scan line and the key line the key is wired to. Both are
+/// indexes starting at 0, so `k` 0 is the `K1` pin of the datasheet.
+///
+/// # Example
+///
+/// ```
+/// use ht16k33::KeyLocation;
+/// use ht16k33::ValidationError;
+/// # fn main() -> Result<(), ValidationError>{
+///
+/// // Key on KS1 and K13.
+/// let location = KeyLocation::new(1, 12)?;
+///
+/// assert_eq!(1, location.ks());
+/// assert_eq!(12, location.k());
+///
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct KeyLocation {
+    ks: u8,
+    k: u8,
+}
+
+impl KeyLocation {
+    /// Create a `KeyLocation` with the given `ks` and `k` values.
+    ///
+    /// # Errors
+    ///
+    /// The `ks` and `k` values are validated to be within their respective [`KEY_SCANS_SIZE`] and
+    /// [`KEY_LINES_SIZE`] ranges of the device. If validation fails then
+    /// [`ht16k33::ValidationError::ValueTooLarge`] is returned.
+    ///
+    /// [`KEY_SCANS_SIZE`]: constant.KEY_SCANS_SIZE.html
+    /// [`KEY_LINES_SIZE`]: constant.KEY_LINES_SIZE.html
+    /// [`ht16k33::ValidationError::ValueTooLarge`]: enum.ValidationError.html#variant.ValueTooLarge
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(ks: u8, k: u8) -> Result<Self, ValidationError> {
+        if ks >= KEY_SCANS_SIZE as u8 {
+            return Err(ValidationError::ValueTooLarge {
+                name: "ks",
+                value: ks,
+                limit: KEY_SCANS_SIZE as u8,
+                inclusive: false,
+            });
+        }
+
+        if k >= KEY_LINES_SIZE as u8 {
+            return Err(ValidationError::ValueTooLarge {
+                name: "k",
+                value: k,
+                limit: KEY_LINES_SIZE as u8,
+                inclusive: false,
+            });
+        }
+
+        Ok(KeyLocation { ks, k })
+    }
+
+    // Create a `KeyLocation` from values that are already known to be in range.
+    pub(crate) const fn new_unchecked(ks: u8, k: u8) -> Self {
+        KeyLocation { ks, k }
+    }
+
+    /// Return the key scan line index.
+    pub const fn ks(self) -> u8 {
+        self.ks
+    }
+
+    /// Return the key line index.
+    pub const fn k(self) -> u8 {
+        self.k
+    }
+
+    // Return the key data RAM byte index and bit mask for this key.
+    pub(crate) const fn key_data_bit(self) -> (usize, u8) {
+        (
+            self.ks as usize * 2 + self.k as usize / 8,
+            1 << (self.k % 8),
+        )
+    }
+}
+
+impl fmt::Display for KeyLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(ks: {}, k: {})", self.ks, self.k)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new() {
+        let location = KeyLocation::new(2, 12).unwrap();
+
+        assert_eq!(2, location.ks());
+        assert_eq!(12, location.k());
+    }
+
+    #[test]
+    #[should_panic]
+    fn ks_too_large() {
+        let _ = KeyLocation::new(KEY_SCANS_SIZE as u8, 0).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn k_too_large() {
+        let _ = KeyLocation::new(0, KEY_LINES_SIZE as u8).unwrap();
+    }
+
+    #[test]
+    fn key_data_bit() {
+        assert_eq!(
+            (0, 0b0000_0001),
+            KeyLocation::new(0, 0).unwrap().key_data_bit()
+        );
+        assert_eq!(
+            (1, 0b0000_0001),
+            KeyLocation::new(0, 8).unwrap().key_data_bit()
+        );
+        assert_eq!(
+            (5, 0b0001_0000),
+            KeyLocation::new(2, 12).unwrap().key_data_bit()
+        );
+    }
+
+    #[test]
+    fn display() {
+        extern crate std;
+        use std::string::ToString;
+
+        assert_eq!("(ks: 1, k: 7)", KeyLocation::new(1, 7).unwrap().to_string());
+    }
+}
@@ -5,6 +5,9 @@ use crate::types::DisplayDataAddress;
 
 use core::fmt;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 /// Represents the LED location.
 ///
 /// The LED location is a ([`DisplayDataAddress`], [`DisplayData`]) pair, created from a validated
@@ -44,7 +47,10 @@ pub struct LedLocation {
 
 impl fmt::Display for LedLocation {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "LedLocation(row: {}, common: {})", self.row, self.common)
+        match self.common.common_index() {
+            Some(common) => write!(f, "(row: {}, common: {})", self.row_index(), common),
+            None => write!(f, "(row: {}, common: {})", self.row_index(), self.common),
+        }
     }
 }
 
@@ -87,25 +93,141 @@ impl LedLocation {
             });
         }
 
+        let row = DisplayDataAddress::from_bits_truncate(row);
+        let common = DisplayData::from_common(common)?;
+
+        Ok(LedLocation { row, common })
+    }
+
+    /// Create an `LedLocation` with the given `row` and `common` values in a `const` context.
+    ///
+    /// This is the building block for the [`leds!`] macro; use [`new`] to validate runtime values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `row` or `common` values are outside their respective [`ROWS_SIZE`] and [`COMMONS_SIZE`]
+    /// ranges of the device. When evaluated in a `const` context this is a compile error instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ht16k33::LedLocation;
+    ///
+    /// const LOCATION: LedLocation = LedLocation::new_const(1, 2);
+    ///
+    /// assert_eq!(ht16k33::DisplayDataAddress::ROW_1, LOCATION.row);
+    /// assert_eq!(ht16k33::DisplayData::COMMON_2, LOCATION.common);
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use ht16k33::LedLocation;
+    ///
+    /// const LOCATION: LedLocation = LedLocation::new_const(16, 2);
+    /// # let _ = LOCATION;
+    /// ```
+    ///
+    /// [`leds!`]: macro.leds.html
+    /// [`new`]: struct.LedLocation.html#method.new
+    /// [`ROWS_SIZE`]: constant.ROWS_SIZE.html
+    /// [`COMMONS_SIZE`]: constant.COMMONS_SIZE.html
+    pub const fn new_const(row: u8, common: u8) -> Self {
+        if row >= ROWS_SIZE as u8 {
+            panic!("'row' value must be less than ROWS_SIZE");
+        }
+
         if common >= COMMONS_SIZE as u8 {
-            return Err(ValidationError::ValueTooLarge {
-                name: "common",
-                value: common,
-                limit: COMMONS_SIZE as u8,
-                inclusive: false,
-            });
+            panic!("'common' value must be less than COMMONS_SIZE");
         }
 
-        let row = DisplayDataAddress::from_bits_truncate(row);
-        let common = DisplayData::from_bits_truncate(1 << common);
+        LedLocation {
+            row: DisplayDataAddress::from_bits_truncate(row),
+            common: DisplayData::from_bits_truncate(1 << common),
+        }
+    }
 
-        Ok(LedLocation { row, common })
+    /// Create an `LedLocation` with the given `row` and `common` values, without validating them.
+    ///
+    /// For hot paths, e.g. per-pixel loops, where the values are already known to be in range. The values are
+    /// only checked by `debug_assert!`, so out-of-range values panic in debug builds and produce a wrong (but
+    /// still valid) location in release builds.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ht16k33::LedLocation;
+    ///
+    /// for row in 0..ht16k33::ROWS_SIZE as u8 {
+    ///     let location = LedLocation::new_unchecked(row, 2);
+    ///
+    ///     assert_eq!(row as usize, location.row_as_index());
+    /// }
+    /// ```
+    pub fn new_unchecked(row: u8, common: u8) -> Self {
+        debug_assert!(
+            row < ROWS_SIZE as u8,
+            "'row' value must be less than ROWS_SIZE"
+        );
+        debug_assert!(
+            common < COMMONS_SIZE as u8,
+            "'common' value must be less than COMMONS_SIZE"
+        );
+
+        LedLocation {
+            row: DisplayDataAddress::from_bits_truncate(row),
+            common: DisplayData::from_bits_truncate(1u8.wrapping_shl(u32::from(common))),
+        }
     }
 
     /// Return the `row` value.
     pub fn row_as_index(self) -> usize {
         self.row.bits() as usize
     }
+
+    /// Return the `row` value as a `u8`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ht16k33::LedLocation;
+    /// # use ht16k33::ValidationError;
+    /// # fn main() -> Result<(), ValidationError> {
+    ///
+    /// let location = LedLocation::new(3, 1)?;
+    ///
+    /// assert_eq!(3u8, location.row_index());
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn row_index(self) -> u8 {
+        self.row.bits()
+    }
+
+    /// Return the `common` value as a `u8`.
+    ///
+    /// The `common` data of an `LedLocation` created by one of the constructors has exactly one LED enabled.
+    /// Otherwise the lowest enabled `common` is returned, or [`COMMONS_SIZE`] if none are enabled, e.g. for the
+    /// `default()` location.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ht16k33::LedLocation;
+    /// # use ht16k33::ValidationError;
+    /// # fn main() -> Result<(), ValidationError> {
+    ///
+    /// let location = LedLocation::new(3, 1)?;
+    ///
+    /// assert_eq!(1u8, location.common_index());
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`COMMONS_SIZE`]: constant.COMMONS_SIZE.html
+    pub fn common_index(self) -> u8 {
+        self.common.bits().trailing_zeros() as u8
+    }
 }
 
 #[cfg(test)]
@@ -152,9 +274,108 @@ mod tests {
         let _ = LedLocation::new(0, 8).unwrap();
     }
 
+    #[test]
+    fn new_const() {
+        const LOCATION: LedLocation = LedLocation::new_const(15, 7);
+
+        assert_eq!(LedLocation::new(15, 7).unwrap(), LOCATION);
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_const_row_too_large() {
+        let _ = LedLocation::new_const(16, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_const_common_too_large() {
+        let _ = LedLocation::new_const(0, 8);
+    }
+
+    #[test]
+    fn new_unchecked() {
+        for row in 0..ROWS_SIZE as u8 {
+            for common in 0..COMMONS_SIZE as u8 {
+                assert_eq!(
+                    LedLocation::new(row, common).unwrap(),
+                    LedLocation::new_unchecked(row, common)
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(debug_assertions)]
+    fn new_unchecked_too_large() {
+        let _ = LedLocation::new_unchecked(16, 0);
+    }
+
     #[test]
     fn row_as_index() {
         let location = LedLocation::new(2, 2).unwrap();
         assert_eq!(2usize, location.row_as_index());
     }
+
+    #[test]
+    fn row_index() {
+        let location = LedLocation::new(3, 1).unwrap();
+        assert_eq!(3u8, location.row_index());
+    }
+
+    #[test]
+    fn common_index() {
+        let location = LedLocation::new(3, 1).unwrap();
+        assert_eq!(1u8, location.common_index());
+    }
+
+    #[test]
+    fn common_index_default() {
+        assert_eq!(COMMONS_SIZE as u8, LedLocation::default().common_index());
+    }
+
+    #[test]
+    fn display() {
+        extern crate std;
+        use std::string::ToString;
+
+        let location = LedLocation::new(3, 1).unwrap();
+        assert_eq!("(row: 3, common: 1)", location.to_string());
+
+        let location = LedLocation::default();
+        assert_eq!(
+            "(row: 0, common: DisplayData::COMMON_NONE)",
+            location.to_string()
+        );
+    }
+
+    #[test]
+    fn new_exhaustive() {
+        extern crate std;
+        use std::string::ToString;
+
+        let mut valid = 0;
+
+        for row in 0..=255u8 {
+            for common in 0..=255u8 {
+                match LedLocation::new(row, common) {
+                    Ok(location) => {
+                        valid += 1;
+                        assert!((row as usize) < ROWS_SIZE && (common as usize) < COMMONS_SIZE);
+                        assert_eq!(row, location.row_index());
+                        assert_eq!(row as usize, location.row_as_index());
+                        assert_eq!(common, location.common_index());
+                        assert_eq!(location, LedLocation::new_unchecked(row, common));
+                        assert!(!location.to_string().is_empty());
+                    }
+                    Err(_) => {
+                        assert!((row as usize) >= ROWS_SIZE || (common as usize) >= COMMONS_SIZE)
+                    }
+                }
+            }
+        }
+
+        assert_eq!(ROWS_SIZE * COMMONS_SIZE, valid);
+    }
 }
@@ -1,13 +1,31 @@
+mod bit_order;
+mod capabilities;
+mod device_defaults;
 mod dimming;
 mod display;
 mod display_data;
 mod display_data_address;
+mod display_view;
+mod init_options;
+mod key_data;
+mod key_location;
 mod led_location;
 mod oscillator;
+mod power_state;
+mod region;
 
+pub use self::bit_order::BitOrder;
+pub use self::capabilities::Capabilities;
+pub use self::device_defaults::DeviceDefaults;
 pub use self::dimming::Dimming;
 pub use self::display::Display;
-pub use self::display_data::DisplayData;
+pub use self::display_data::{CommonIndexes, DisplayData};
 pub use self::display_data_address::DisplayDataAddress;
+pub use self::display_view::DisplayView;
+pub use self::init_options::{InitOptions, RampMs};
+pub use self::key_data::{KeyData, PressedKeys};
+pub use self::key_location::KeyLocation;
 pub use self::led_location::LedLocation;
 pub use self::oscillator::Oscillator;
+pub use self::power_state::PowerState;
+pub use self::region::Region;
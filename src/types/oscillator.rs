@@ -44,4 +44,16 @@ mod tests {
             "Oscillator default is OFF"
         );
     }
+
+    #[test]
+    fn from_bits_exhaustive() {
+        extern crate std;
+        use std::string::ToString;
+
+        for value in 0..=255u8 {
+            let oscillator = Oscillator::from_bits_truncate(value);
+            assert_eq!(value & 0b0010_0001, oscillator.bits());
+            assert!(oscillator.to_string().starts_with("Oscillator::"));
+        }
+    }
 }
@@ -0,0 +1,58 @@
+use crate::types::Oscillator;
+
+use core::fmt;
+
+/// The power state of the HT16K33 chip, set by its system oscillator.
+///
+/// In standby the display RAM can still be written, but the display is blank and keyscan is stopped.
+///
+/// # Example
+///
+/// ```
+/// use ht16k33::{Oscillator, PowerState};
+///
+/// assert_eq!(PowerState::Standby, PowerState::from(Oscillator::OFF));
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PowerState {
+    /// The oscillator is off.
+    ///
+    /// *This is the power-on state.*
+    Standby,
+    /// The oscillator is on.
+    Normal,
+}
+
+impl From<Oscillator> for PowerState {
+    fn from(oscillator: Oscillator) -> Self {
+        if oscillator.contains(Oscillator::ON) {
+            PowerState::Normal
+        } else {
+            PowerState::Standby
+        }
+    }
+}
+
+impl fmt::Display for PowerState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PowerState::Standby => write!(f, "PowerState::Standby"),
+            PowerState::Normal => write!(f, "PowerState::Normal"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_oscillator() {
+        assert_eq!(PowerState::Standby, PowerState::from(Oscillator::OFF));
+        assert_eq!(PowerState::Normal, PowerState::from(Oscillator::ON));
+        assert_eq!(
+            PowerState::Normal,
+            PowerState::from(Oscillator::COMMAND | Oscillator::ON)
+        );
+    }
+}
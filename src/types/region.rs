@@ -0,0 +1,289 @@
+use crate::constants::{COMMONS_SIZE, ROWS_SIZE};
+use crate::errors::ValidationError;
+use crate::types::{DisplayData, LedLocation};
+
+use core::fmt;
+
+/// Represents a rectangular region of LEDs.
+///
+/// The region is the ROWs `[row_start, row_end)` by the COMMONs `[common_start, common_end)`, it is never empty.
+///
+/// # Example
+///
+/// ```
+/// use ht16k33::{LedLocation, Region};
+/// use ht16k33::ValidationError;
+/// # fn main() -> Result<(), ValidationError>{
+///
+/// // ROWs 2 to 4, COMMONs 0 to 1.
+/// let region = Region::new(2, 5, 0, 2)?;
+///
+/// assert!(region.contains(LedLocation::new(4, 1)?));
+/// assert!(!region.contains(LedLocation::new(5, 1)?));
+///
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Region {
+    row_start: u8,
+    row_end: u8,
+    common_start: u8,
+    common_end: u8,
+}
+
+impl Region {
+    /// Create a `Region` with the given ROW and COMMON ranges, the ends are exclusive.
+    ///
+    /// # Errors
+    ///
+    /// Each range is validated to be non-empty and to end within the [`ROWS_SIZE`] or [`COMMONS_SIZE`] of the
+    /// device. If validation fails then [`ht16k33::ValidationError::RangeOutOfBounds`] is returned.
+    ///
+    /// # Error Example
+    ///
+    /// ```
+    /// use ht16k33::{Region, ValidationError};
+    ///
+    /// match Region::new(0, 1, 4, 9) {
+    ///     Err(ValidationError::RangeOutOfBounds { axis, .. }) => assert_eq!("common", axis),
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    ///
+    /// [`ROWS_SIZE`]: constant.ROWS_SIZE.html
+    /// [`COMMONS_SIZE`]: constant.COMMONS_SIZE.html
+    /// [`ht16k33::ValidationError::RangeOutOfBounds`]: enum.ValidationError.html#variant.RangeOutOfBounds
+    pub fn new(
+        row_start: u8,
+        row_end: u8,
+        common_start: u8,
+        common_end: u8,
+    ) -> Result<Self, ValidationError> {
+        Self::validate("row", row_start, row_end, ROWS_SIZE as u8)?;
+        Self::validate("common", common_start, common_end, COMMONS_SIZE as u8)?;
+
+        Ok(Region {
+            row_start,
+            row_end,
+            common_start,
+            common_end,
+        })
+    }
+
+    /// Return the region covering the whole display.
+    pub const fn all() -> Self {
+        Region {
+            row_start: 0,
+            row_end: ROWS_SIZE as u8,
+            common_start: 0,
+            common_end: COMMONS_SIZE as u8,
+        }
+    }
+
+    /// Return the first ROW of the region.
+    pub const fn row_start(self) -> u8 {
+        self.row_start
+    }
+
+    /// Return the ROW after the last ROW of the region.
+    pub const fn row_end(self) -> u8 {
+        self.row_end
+    }
+
+    /// Return the first COMMON of the region.
+    pub const fn common_start(self) -> u8 {
+        self.common_start
+    }
+
+    /// Return the COMMON after the last COMMON of the region.
+    pub const fn common_end(self) -> u8 {
+        self.common_end
+    }
+
+    /// Return whether the LED is inside the region.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - The LED location to check.
+    pub fn contains(self, location: LedLocation) -> bool {
+        (self.row_start..self.row_end).contains(&location.row_index())
+            && (self.common_start..self.common_end).contains(&location.common_index())
+    }
+
+    /// Return the region covered by both regions, or `None` if they do not overlap.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The region to intersect with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ht16k33::{Region, ValidationError};
+    /// # fn main() -> Result<(), ValidationError>{
+    ///
+    /// let player = Region::new(2, 4, 2, 4)?;
+    /// let enemy = Region::new(3, 6, 3, 6)?;
+    ///
+    /// assert_eq!(Some(Region::new(3, 4, 3, 4)?), player.intersection(enemy));
+    /// assert_eq!(None, player.intersection(Region::new(4, 6, 0, 8)?));
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn intersection(self, other: Region) -> Option<Region> {
+        let row_start = self.row_start.max(other.row_start);
+        let row_end = self.row_end.min(other.row_end);
+        let common_start = self.common_start.max(other.common_start);
+        let common_end = self.common_end.min(other.common_end);
+
+        if row_start >= row_end || common_start >= common_end {
+            return None;
+        }
+
+        Some(Region {
+            row_start,
+            row_end,
+            common_start,
+            common_end,
+        })
+    }
+
+    /// Return whether the regions share any LED.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The region to check.
+    pub fn intersects(self, other: Region) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    // Return the COMMONs of the region in a ROW.
+    pub(crate) fn commons(self) -> DisplayData {
+        let width = self.common_end - self.common_start;
+
+        // `width` is at most 8, so widen before shifting.
+        DisplayData::from_bits_truncate((((1u16 << width) - 1) << self.common_start) as u8)
+    }
+
+    // Validate that `[start, end)` is a non-empty range ending at or before `limit`.
+    fn validate(axis: &'static str, start: u8, end: u8, limit: u8) -> Result<(), ValidationError> {
+        if start >= end || end > limit {
+            return Err(ValidationError::RangeOutOfBounds {
+                axis,
+                start,
+                end,
+                limit,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "(rows: [{}, {}), commons: [{}, {}))",
+            self.row_start, self.row_end, self.common_start, self.common_end
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new() {
+        let region = Region::new(1, 3, 2, 8).unwrap();
+
+        assert_eq!(1, region.row_start());
+        assert_eq!(3, region.row_end());
+        assert_eq!(2, region.common_start());
+        assert_eq!(8, region.common_end());
+
+        assert_eq!(Region::all(), Region::new(0, 16, 0, 8).unwrap());
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        for &(rows, commons, axis) in [
+            ((0, 17), (0, 8), "row"),
+            ((3, 3), (0, 8), "row"),
+            ((4, 3), (0, 8), "row"),
+            ((0, 16), (0, 9), "common"),
+            ((0, 16), (5, 5), "common"),
+        ]
+        .iter()
+        {
+            match Region::new(rows.0, rows.1, commons.0, commons.1) {
+                Err(ValidationError::RangeOutOfBounds { axis: error, .. }) => {
+                    assert_eq!(axis, error)
+                }
+                result => panic!("unexpected result: {:?}", result),
+            }
+        }
+    }
+
+    #[test]
+    fn contains() {
+        let region = Region::new(1, 3, 2, 4).unwrap();
+
+        for row in 0..ROWS_SIZE as u8 {
+            for common in 0..COMMONS_SIZE as u8 {
+                let location = LedLocation::new(row, common).unwrap();
+
+                assert_eq!(
+                    (1..3).contains(&row) && (2..4).contains(&common),
+                    region.contains(location),
+                    "{}",
+                    location
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn intersection() {
+        let region = Region::new(2, 6, 1, 5).unwrap();
+
+        assert_eq!(Some(region), region.intersection(region));
+        assert_eq!(Some(region), region.intersection(Region::all()));
+        assert_eq!(
+            Some(Region::new(4, 6, 3, 5).unwrap()),
+            region.intersection(Region::new(4, 10, 3, 8).unwrap())
+        );
+
+        // Touching edges do not overlap, the ends are exclusive.
+        assert_eq!(None, region.intersection(Region::new(6, 8, 1, 5).unwrap()));
+        assert_eq!(None, region.intersection(Region::new(2, 6, 5, 8).unwrap()));
+        assert!(!region.intersects(Region::new(0, 2, 0, 8).unwrap()));
+        assert!(region.intersects(Region::new(0, 3, 0, 2).unwrap()));
+
+        // Every LED in the intersection is in both regions.
+        let other = Region::new(0, 4, 3, 8).unwrap();
+        let overlap = region.intersection(other).unwrap();
+        for row in 0..ROWS_SIZE as u8 {
+            for common in 0..COMMONS_SIZE as u8 {
+                let location = LedLocation::new(row, common).unwrap();
+
+                assert_eq!(
+                    region.contains(location) && other.contains(location),
+                    overlap.contains(location)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn commons() {
+        assert_eq!(
+            DisplayData::COMMON_2 | DisplayData::COMMON_3,
+            Region::new(0, 1, 2, 4).unwrap().commons()
+        );
+        assert_eq!(DisplayData::all(), Region::all().commons());
+    }
+}
@@ -0,0 +1,306 @@
+//! Differential tests between the driver's cached state and the emulated chip state of the `I2cMock`.
+//!
+//! Random sequences of public API calls are applied to the driver, and the driver's view of the chip must match
+//! the emulated chip after every call.
+use embedded_hal::blocking::i2c::{Write, WriteRead};
+use embedded_hal_mock::delay::MockNoop;
+use ht16k33::i2c_mock::{I2cMock, I2cMockError};
+use ht16k33::{
+    BitOrder, Dimming, Display, DisplayData, DisplayDataAddress, Effect, Invert, LedLocation,
+    Oscillator, Region, COMMONS_SIZE, HT16K33, ROWS_SIZE,
+};
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const SEQUENCES: u32 = 500;
+const MAX_SEQUENCE_LENGTH: u32 = 64;
+
+/// Minimal xorshift PRNG, so the sequences are reproducible from the seed.
+struct Rng(u32);
+
+impl Rng {
+    fn next(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0
+    }
+
+    fn below(&mut self, limit: u32) -> u8 {
+        (self.next() % limit) as u8
+    }
+
+    fn bool(&mut self) -> bool {
+        self.next() & 1 == 1
+    }
+
+    fn location(&mut self) -> LedLocation {
+        LedLocation::new(
+            self.below(ROWS_SIZE as u32),
+            self.below(COMMONS_SIZE as u32),
+        )
+        .unwrap()
+    }
+}
+
+/// The emulated chip, shared by the driver's own I2C device and the borrowed-bus `_with` methods.
+#[derive(Clone)]
+struct SharedI2c(Rc<RefCell<I2cMock>>);
+
+impl Write for SharedI2c {
+    type Error = I2cMockError;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.0.borrow_mut().write(address, bytes)
+    }
+}
+
+impl WriteRead for SharedI2c {
+    type Error = I2cMockError;
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.0.borrow_mut().write_read(address, bytes, buffer)
+    }
+}
+
+const DISPLAYS: [Display; 5] = [
+    Display::ON,
+    Display::OFF,
+    Display::HALF_HZ,
+    Display::ONE_HZ,
+    Display::TWO_HZ,
+];
+
+impl Rng {
+    fn oscillator(&mut self) -> Oscillator {
+        if self.bool() {
+            Oscillator::ON
+        } else {
+            Oscillator::OFF
+        }
+    }
+
+    fn display(&mut self) -> Display {
+        DISPLAYS[self.below(DISPLAYS.len() as u32) as usize]
+    }
+
+    fn dimming(&mut self) -> Dimming {
+        Dimming::from_u8(self.below(16)).unwrap()
+    }
+
+    fn frame(&mut self) -> [DisplayData; ROWS_SIZE] {
+        let mut frame = [DisplayData::empty(); ROWS_SIZE];
+
+        for row in frame.iter_mut() {
+            *row = DisplayData::from_bits_truncate(self.below(256));
+        }
+
+        frame
+    }
+
+    fn row_map(&mut self) -> [DisplayDataAddress; ROWS_SIZE] {
+        let mut row_map = [DisplayDataAddress::ROW_0; ROWS_SIZE];
+
+        for (row, ram_row) in row_map.iter_mut().enumerate() {
+            *ram_row = DisplayDataAddress::from_bits_truncate(row as u8);
+        }
+
+        // Fisher-Yates shuffle.
+        for row in (1..ROWS_SIZE).rev() {
+            row_map.swap(row, self.below(row as u32 + 1) as usize);
+        }
+
+        row_map
+    }
+}
+
+/// Return the display RAM the chip must hold for the frame, with the canary and the board wiring applied.
+fn expected_ram(ht16k33: &HT16K33<SharedI2c>, frame: &[DisplayData; ROWS_SIZE]) -> [u8; ROWS_SIZE] {
+    let mut state = ht16k33.state().clone();
+    *state.display_buffer_mut() = *frame;
+
+    // The canary is always written to the chip.
+    if let Some(canary) = ht16k33.canary() {
+        state.display_buffer_mut()[canary.row_as_index()].insert(canary.common);
+    }
+
+    let mut ram = [0u8; ROWS_SIZE];
+    for (ram_row, data) in ram.iter_mut().zip(state.display_ram().iter()) {
+        *ram_row = data.bits();
+    }
+
+    ram
+}
+
+/// Assert that the emulated chip shows the frame.
+fn assert_shows(
+    ht16k33: &HT16K33<SharedI2c>,
+    i2c: &SharedI2c,
+    frame: &[DisplayData; ROWS_SIZE],
+    context: &str,
+) {
+    assert_eq!(
+        expected_ram(ht16k33, frame),
+        i2c.0.borrow().data_values,
+        "{}",
+        context
+    );
+}
+
+/// Assert that the driver's view of the chip matches the emulated chip.
+fn assert_matches(ht16k33: &HT16K33<SharedI2c>, i2c: &SharedI2c, context: &str) {
+    let chip = i2c.0.borrow();
+
+    assert!(ht16k33.is_synced(), "{}", context);
+    assert_eq!(*ht16k33.oscillator(), chip.oscillator, "{}", context);
+    assert_eq!(*ht16k33.display(), chip.display, "{}", context);
+    assert_eq!(*ht16k33.dimming(), chip.dimming, "{}", context);
+    drop(chip);
+
+    if !ht16k33.needs_flush() {
+        assert_shows(ht16k33, i2c, ht16k33.display_buffer(), context);
+    }
+}
+
+fn apply_random_call(
+    rng: &mut Rng,
+    ht16k33: &mut HT16K33<SharedI2c>,
+    i2c: &mut SharedI2c,
+    context: &str,
+) {
+    match rng.below(20) {
+        0 => ht16k33.update_display_buffer(rng.location(), rng.bool()),
+        1 => ht16k33.set_led(rng.location(), rng.bool()).unwrap(),
+        2 => ht16k33.clear_display_buffer(),
+        3 => ht16k33.write_display_buffer().unwrap(),
+        4 => ht16k33.read_display_buffer().unwrap(),
+        5 => ht16k33.set_display(rng.display()).unwrap(),
+        6 => ht16k33.set_dimming(rng.dimming()).unwrap(),
+        7 => ht16k33.set_oscillator(rng.oscillator()).unwrap(),
+        8 => {
+            let mut commons = [(DisplayDataAddress::ROW_0, DisplayData::COMMON_NONE); 4];
+            let length = rng.below(commons.len() as u32 + 1) as usize;
+
+            for common in commons.iter_mut() {
+                *common = (
+                    DisplayDataAddress::from_bits_truncate(rng.below(ROWS_SIZE as u32)),
+                    DisplayData::from_bits_truncate(rng.below(256)),
+                );
+            }
+
+            ht16k33.write_commons(&commons[..length]).unwrap()
+        }
+        9 => {
+            let canary = if rng.bool() {
+                Some(rng.location())
+            } else {
+                None
+            };
+            ht16k33.set_canary(canary)
+        }
+        10 => ht16k33.set_max_write_len(rng.below(ROWS_SIZE as u32 + 2) as usize),
+        11 => ht16k33
+            .flash_alert(rng.below(3), 1, 1, &mut MockNoop::new())
+            .unwrap(),
+        12 => {
+            let leds: Vec<_> = (0..rng.below(5))
+                .map(|_| (rng.location(), rng.bool()))
+                .collect();
+            ht16k33.set_leds(leds).unwrap()
+        }
+        13 => ht16k33.write_dirty().unwrap(),
+        14 => {
+            if rng.bool() {
+                ht16k33.set_overlay(rng.frame(), rng.below(3) as u32);
+            }
+
+            ht16k33.tick().unwrap();
+
+            // While the overlay is shown the display buffer is not on the chip, and so it is not synchronized.
+            if let Some(overlay) = ht16k33.overlay() {
+                let overlay = *overlay;
+                assert!(ht16k33.needs_flush(), "{}", context);
+                assert_shows(ht16k33, i2c, &overlay, context);
+            }
+        }
+        15 => match rng.below(6) {
+            0 => ht16k33
+                .set_led_with(i2c, rng.location(), rng.bool())
+                .unwrap(),
+            1 => ht16k33.write_display_buffer_with(i2c).unwrap(),
+            2 => ht16k33.set_oscillator_with(i2c, rng.oscillator()).unwrap(),
+            3 => ht16k33.set_display_with(i2c, rng.display()).unwrap(),
+            4 => ht16k33.set_dimming_with(i2c, rng.dimming()).unwrap(),
+            _ => {
+                let key_data = ht16k33.read_key_data_with(i2c).unwrap();
+                assert_eq!(
+                    i2c.0.borrow().key_values,
+                    *ht16k33.key_buffer(),
+                    "{}",
+                    context
+                );
+                assert_eq!(key_data, ht16k33.read_key_data().unwrap(), "{}", context);
+            }
+        },
+        16 => ht16k33.set_row_map(rng.row_map()).unwrap(),
+        17 => {
+            let bit_order = if rng.bool() {
+                BitOrder::Normal
+            } else {
+                BitOrder::Reversed
+            };
+            ht16k33.set_bit_order(bit_order)
+        }
+        18 => {
+            ht16k33.write_display_buffer_effect(&mut Invert, 0).unwrap();
+
+            // The effect is only applied to the chip, so check it and then restore the display buffer.
+            let mut frame = *ht16k33.display_buffer();
+            Invert.apply(&mut frame, 0);
+            assert_shows(ht16k33, i2c, &frame, context);
+
+            ht16k33.write_display_buffer().unwrap();
+        }
+        _ => {
+            let (row, common) = (rng.below(ROWS_SIZE as u32), rng.below(COMMONS_SIZE as u32));
+            let region = Region::new(
+                row,
+                row + 1 + rng.below(ROWS_SIZE as u32 - row as u32),
+                common,
+                common + 1 + rng.below(COMMONS_SIZE as u32 - common as u32),
+            )
+            .unwrap();
+            ht16k33.fill_region(region, rng.bool())
+        }
+    }
+}
+
+#[test]
+fn driver_matches_emulator() {
+    for seed in 1..=SEQUENCES {
+        let mut rng = Rng(seed);
+
+        let mut i2c = SharedI2c(Rc::new(RefCell::new(I2cMock::new())));
+        let mut ht16k33 = HT16K33::new(i2c.clone(), 0);
+        ht16k33.initialize().unwrap();
+        assert_matches(&ht16k33, &i2c, &format!("seed [{}] initialize", seed));
+
+        for step in 0..rng.below(MAX_SEQUENCE_LENGTH) {
+            let context = format!("seed [{}] step [{}]", seed, step);
+
+            apply_random_call(&mut rng, &mut ht16k33, &mut i2c, &context);
+            assert_matches(&ht16k33, &i2c, &context);
+        }
+
+        ht16k33.clear_overlay();
+        ht16k33.write_display_buffer().unwrap();
+        assert!(!ht16k33.needs_flush());
+        assert_matches(&ht16k33, &i2c, &format!("seed [{}] final", seed));
+    }
+}